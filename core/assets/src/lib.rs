@@ -4,7 +4,7 @@ use std::{
 };
 
 use rgine_modules::{
-    events::{EventQueue, Listener},
+    events::{EventQueue, Listener, Query, Request},
     utils::Take,
     AnyResult, Engine, Module,
 };
@@ -16,6 +16,25 @@ pub enum AssetsEvent {
     Reset,
 }
 
+/// Asks the [`AssetsModule`] whether the asset of type `type_id` is loaded, see [`EventQueue::query`].
+pub struct IsAssetLoaded(pub TypeId);
+
+impl IsAssetLoaded {
+    pub fn of<T: 'static>() -> Self {
+        Self(TypeId::of::<T>())
+    }
+}
+
+impl Request for IsAssetLoaded {
+    type Reply = AssetLoaded;
+}
+
+/// Reply to [`IsAssetLoaded`]
+pub struct AssetLoaded {
+    pub type_id: TypeId,
+    pub loaded: bool,
+}
+
 pub struct AssetsModule {
     loaders: HashMap<TypeId, AssetLoader>,
     loaded: HashMap<TypeId, Box<dyn Any>>,
@@ -36,7 +55,7 @@ impl AssetsModule {
 }
 
 impl Module for AssetsModule {
-    type ListeningTo = (AssetsEvent,);
+    type ListeningTo = (AssetsEvent, Query<IsAssetLoaded>);
     fn new(_: &mut Engine) -> AnyResult<Self> {
         Ok(Self {
             loaders: HashMap::new(),
@@ -57,6 +76,19 @@ impl Listener<AssetsEvent> for AssetsModule {
     }
 }
 
+impl Listener<Query<IsAssetLoaded>> for AssetsModule {
+    fn on_event(&mut self, query: &mut Query<IsAssetLoaded>, queue: &mut EventQueue) {
+        let type_id = query.0;
+        query.reply(
+            queue,
+            AssetLoaded {
+                type_id,
+                loaded: self.is_loaded(&type_id),
+            },
+        );
+    }
+}
+
 pub trait AssetsEventQueueExt {
     fn load_asset<T: 'static>(&mut self, asset: T);
 }
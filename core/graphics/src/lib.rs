@@ -1,7 +1,6 @@
 use ctx::{Frame, GraphicsCtx};
 use rgine_modules::{
     events::{EventQueue, Listener},
-    standards::ShutdownEvent,
     AnyResult, Dependency, Engine, Module,
 };
 use rgine_platform::window::module::{
@@ -37,7 +36,6 @@ impl Module for GraphicsModule {
         SurfaceResizeEvent,
         WindowRenderReadyEvent,
         RenderPresentEvent,
-        ShutdownEvent,
     );
 
     fn new(ctx: &mut Engine) -> AnyResult<Self> {
@@ -49,6 +47,12 @@ impl Module for GraphicsModule {
             current_frame: None,
         })
     }
+
+    fn on_unload(&mut self) {
+        // The surface must be released while the window of the platform module is still alive.
+        self.current_frame.take();
+        self.ctx.take();
+    }
 }
 impl Listener<WindowReadyEvent> for GraphicsModule {
     fn on_event(&mut self, _: &mut WindowReadyEvent, _: &mut EventQueue) {
@@ -82,9 +86,3 @@ impl Listener<RenderPresentEvent> for GraphicsModule {
         });
     }
 }
-impl Listener<ShutdownEvent> for GraphicsModule {
-    fn on_event(&mut self, _: &mut ShutdownEvent, _: &mut EventQueue) {
-        // Prevent STATUS_ACCESS_VIOLATION because of platform dependency dropped before this module and so surface is pointing at null window, i guess?
-        self.ctx.take();
-    }
-}
@@ -0,0 +1,320 @@
+use std::{
+    any::type_name,
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::Engine;
+
+/// Prefix of the environment variables overriding the configuration, see [`Config::with_env`].
+pub const ENV_PREFIX: &str = "RGINE__";
+
+/// Typed section of the configuration, read by modules with [`Engine::config`].
+///
+/// Fields missing from the configuration are taken from [`Default`] as long as the type is annotated with `#[serde(default)]`,
+/// and the whole section defaults if it is missing.
+///
+/// ```ignore
+/// #[derive(Default, Deserialize)]
+/// #[serde(default)]
+/// struct WindowConfig {
+///     title: String,
+/// }
+///
+/// impl ModuleConfig for WindowConfig {
+///     const SECTION: &'static str = "window";
+/// }
+/// ```
+pub trait ModuleConfig: DeserializeOwned + Default + 'static {
+    /// Name of the section, i.e. the `[table]` of a TOML file or the field of a RON file
+    const SECTION: &'static str;
+
+    /// Checks the values once deserialized, the message is reported in [`ConfigError::Validation`].
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Configuration of the modules, split into sections, see [`ModuleConfig`].
+///
+/// Built from a TOML or RON file with [`Config::load`], then overridden by the environment and the command line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    sections: Map<String, Value>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a TOML (`.toml`) or RON (`.ron`) file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        if !matches!(extension, Some("toml" | "ron")) {
+            return Err(ConfigError::UnsupportedFormat(path.to_owned()));
+        }
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        let parse_error = |source: Box<dyn Error>| ConfigError::Parse {
+            path: path.to_owned(),
+            source,
+        };
+        let value: Value = if extension == Some("toml") {
+            toml::from_str(&text).map_err(|e| parse_error(Box::new(e)))?
+        } else {
+            // Parsed as a RON value first, as unnamed structs can't be deserialized into a JSON value directly
+            let value: ron::Value = ron::from_str(&text).map_err(|e| parse_error(Box::new(e)))?;
+            serde_json::to_value(value).map_err(|e| parse_error(Box::new(e)))?
+        };
+        match value {
+            Value::Object(sections) => Ok(Self { sections }),
+            _ => Err(parse_error("expected a map of sections".into())),
+        }
+    }
+
+    /// Loads the configuration from the usual sources, each one overriding the previous:
+    /// - the file given with `--config-file <path>`, or else `config.toml` or `config.ron` if present
+    /// - the environment, see [`Config::with_env`]
+    /// - the command line, see [`Config::with_args`]
+    pub fn from_default_sources() -> Result<Self, ConfigError> {
+        let args = std::env::args_os().collect::<Vec<_>>();
+        let file = args
+            .iter()
+            .position(|arg| arg == "--config-file")
+            .map(|i| {
+                args.get(i + 1)
+                    .map(PathBuf::from)
+                    .ok_or_else(|| ConfigError::Override {
+                        value: "--config-file".to_owned(),
+                        reason: "expected a path after --config-file",
+                    })
+            })
+            .transpose()?
+            .or_else(|| {
+                ["config.toml", "config.ron"]
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .find(|path| path.is_file())
+            });
+
+        let config = match file {
+            Some(path) => Self::load(path)?,
+            None => Self::new(),
+        };
+        config.with_env()?.with_args()
+    }
+
+    /// Applies the environment variables `RGINE__<SECTION>__<KEY>=<value>`, for example `RGINE__WINDOW__TITLE=Game`.
+    ///
+    /// Names are lowercased, nested keys are separated by `__`. Variables whose name or value isn't valid UTF-8 are ignored.
+    pub fn with_env(self) -> Result<Self, ConfigError> {
+        let overrides = std::env::vars_os()
+            .filter_map(|(name, value)| {
+                let key = name
+                    .to_str()?
+                    .strip_prefix(ENV_PREFIX)?
+                    .to_lowercase()
+                    .replace("__", ".");
+                Some(format!("{key}={}", value.to_str()?))
+            })
+            .collect::<Vec<_>>();
+        self.with_overrides(overrides)
+    }
+
+    /// Applies the `--config <section>.<key>=<value>` command line arguments, for example `--config window.title=Game`.
+    ///
+    /// Arguments that aren't valid UTF-8 are ignored, unless given to `--config`.
+    pub fn with_args(self) -> Result<Self, ConfigError> {
+        let mut overrides = Vec::new();
+        let mut args = std::env::args_os();
+        while let Some(arg) = args.next() {
+            let Some(arg) = arg.to_str() else {
+                continue;
+            };
+            if let Some(value) = arg.strip_prefix("--config=") {
+                overrides.push(value.to_owned());
+            } else if arg == "--config" {
+                let value = args.next().ok_or_else(|| ConfigError::Override {
+                    value: arg.to_owned(),
+                    reason: "expected <section>.<key>=<value> after --config",
+                })?;
+                let value = value.into_string().map_err(|value| ConfigError::Override {
+                    value: value.to_string_lossy().into_owned(),
+                    reason: "not valid UTF-8",
+                })?;
+                overrides.push(value);
+            }
+        }
+        self.with_overrides(overrides)
+    }
+
+    /// Sets the values given as `<section>.<key>=<value>`.
+    ///
+    /// Values are parsed as TOML values (`60`, `true`, `[1, 2]`...) and kept as strings if they aren't valid TOML.
+    pub fn with_overrides(
+        mut self,
+        overrides: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, ConfigError> {
+        for entry in overrides {
+            let entry = entry.as_ref();
+            let invalid = |reason| ConfigError::Override {
+                value: entry.to_owned(),
+                reason,
+            };
+            let (path, raw) = entry
+                .split_once('=')
+                .ok_or_else(|| invalid("expected <section>.<key>=<value>"))?;
+            let keys = path.trim().split('.').collect::<Vec<_>>();
+            if keys.len() < 2 || keys.iter().any(|key| key.is_empty()) {
+                return Err(invalid("expected <section>.<key>=<value>"));
+            }
+
+            let mut table = &mut self.sections;
+            for key in &keys[..keys.len() - 1] {
+                let entry = table
+                    .entry(key.to_string())
+                    .or_insert_with(|| Value::Object(Map::new()));
+                table = entry
+                    .as_object_mut()
+                    .ok_or_else(|| invalid("a parent of the key is not a table"))?;
+            }
+            table.insert(keys[keys.len() - 1].to_owned(), parse_value(raw.trim()));
+        }
+        Ok(self)
+    }
+
+    /// Deserializes and validates the section `T`, see [`ModuleConfig`].
+    pub fn get<T: ModuleConfig>(&self) -> Result<T, ConfigError> {
+        let config = match self.sections.get(T::SECTION) {
+            Some(section) => T::deserialize(section).map_err(|source| ConfigError::Invalid {
+                section: T::SECTION,
+                config: type_name::<T>(),
+                source,
+            })?,
+            None => T::default(),
+        };
+        config
+            .validate()
+            .map_err(|message| ConfigError::Validation {
+                section: T::SECTION,
+                message,
+            })?;
+        Ok(config)
+    }
+
+    /// Names of the sections present in the configuration
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.sections.keys().map(String::as_str)
+    }
+}
+
+fn parse_value(raw: &str) -> Value {
+    toml::from_str::<Map<String, Value>>(&format!("value = {raw}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_owned()))
+}
+
+#[derive(Debug)]
+/// Error relative to the configuration
+pub enum ConfigError {
+    /// Error occured while reading the configuration file
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Error occured because the configuration file could not be parsed
+    Parse {
+        path: PathBuf,
+        source: Box<dyn Error>,
+    },
+    /// Error occured because the configuration file is neither TOML nor RON
+    UnsupportedFormat(PathBuf),
+    /// Error occured because an override from the environment or the command line is malformed
+    Override { value: String, reason: &'static str },
+    /// Error occured while deserializing a section
+    Invalid {
+        section: &'static str,
+        config: &'static str,
+        source: serde_json::Error,
+    },
+    /// Error occured because a section was rejected by [`ModuleConfig::validate`]
+    Validation {
+        section: &'static str,
+        message: String,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, .. } => {
+                write!(f, "Failed to read the configuration at {}", path.display())
+            }
+            Self::Parse { path, .. } => {
+                write!(
+                    f,
+                    "The configuration at {} could not be parsed",
+                    path.display()
+                )
+            }
+            Self::UnsupportedFormat(path) => write!(
+                f,
+                "The configuration at {} is neither a .toml nor a .ron file",
+                path.display()
+            ),
+            Self::Override { value, reason } => {
+                write!(
+                    f,
+                    "Invalid configuration override \"{}\": {}",
+                    value, reason
+                )
+            }
+            Self::Invalid {
+                section, config, ..
+            } => write!(
+                f,
+                "The configuration section [{}] is not a valid {}",
+                section, config
+            ),
+            Self::Validation { section, message } => write!(
+                f,
+                "The configuration section [{}] is invalid: {}",
+                section, message
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Parse { source, .. } => Some(source.as_ref()),
+            Self::Invalid { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl Engine {
+    /// Reads the section `T` of the engine configuration, defaulting if it is missing, see [`ModuleConfig`].
+    ///
+    /// Usually called from `Module::new`, where the error can be returned as is.
+    pub fn config<T: ModuleConfig>(&self) -> Result<T, ConfigError> {
+        self.config.get()
+    }
+
+    /// Configuration the engine was created with, see [`Engine::try_with_config`].
+    pub fn config_source(&self) -> &Config {
+        &self.config
+    }
+}
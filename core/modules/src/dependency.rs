@@ -0,0 +1,269 @@
+use std::{
+    any::{type_name, Any},
+    cell::{Cell, Ref, RefCell, RefMut},
+    error::Error,
+    fmt::Display,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    rc::{Rc, Weak},
+};
+
+use crate::Module;
+
+pub(crate) type ModuleState = Rc<ModuleCell>;
+/// Slot filled with the state of a module whenever it is loaded, shared by its soft dependencies.
+pub(crate) type SoftSlot = Rc<RefCell<Weak<ModuleCell>>>;
+
+/// State of a module, shared between the engine and the dependencies on the module.
+pub(crate) struct ModuleCell {
+    pub(crate) name: &'static str,
+    state: RefCell<Box<dyn Any>>,
+    /// Module mutably borrowing the state, either because it is being dispatched to or through a [`DependencyMut`]
+    borrowed_by: Cell<Option<&'static str>>,
+}
+
+impl ModuleCell {
+    pub(crate) fn new<T: Module>(state: T) -> ModuleState {
+        Rc::new(Self {
+            name: type_name::<T>(),
+            state: RefCell::new(Box::new(state)),
+            borrowed_by: Cell::new(None),
+        })
+    }
+
+    pub(crate) fn into_inner(self) -> Box<dyn Any> {
+        self.state.into_inner()
+    }
+
+    pub(crate) fn try_borrow(
+        &self,
+        requested_by: Option<&'static str>,
+    ) -> Result<Ref<'_, Box<dyn Any>>, BorrowError> {
+        self.state
+            .try_borrow()
+            .map_err(|_| self.borrow_error(requested_by))
+    }
+
+    pub(crate) fn try_borrow_mut(
+        &self,
+        requested_by: Option<&'static str>,
+    ) -> Result<StateMut<'_, Box<dyn Any>>, BorrowError> {
+        let inner = self
+            .state
+            .try_borrow_mut()
+            .map_err(|_| self.borrow_error(requested_by))?;
+        self.borrowed_by.set(requested_by);
+        Ok(StateMut {
+            inner,
+            _borrower: BorrowerGuard(&self.borrowed_by),
+        })
+    }
+
+    fn borrow_error(&self, requested_by: Option<&'static str>) -> BorrowError {
+        BorrowError {
+            module: self.name,
+            requested_by,
+            borrowed_by: self.borrowed_by.get(),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Error occuring when the state of a module is accessed while already being borrowed in a conflicting way,
+/// for example when reading a dependency that is currently being dispatched to.
+pub struct BorrowError {
+    /// Module whose state could not be borrowed
+    pub module: &'static str,
+    /// Module that requested the state, `None` if requested from outside of any module
+    pub requested_by: Option<&'static str>,
+    /// Module currently borrowing the state mutably, `None` if unknown or if the state is only being read
+    pub borrowed_by: Option<&'static str>,
+}
+
+impl Display for BorrowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The state of {} requested by {} is already borrowed",
+            self.module,
+            self.requested_by.unwrap_or("the engine")
+        )?;
+        match self.borrowed_by {
+            Some(borrowed_by) => write!(f, " mutably by {}", borrowed_by),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Error for BorrowError {}
+
+/// A mutable borrow of a module state, see [`DependencyMut::write_state`].
+pub struct StateMut<'a, T: ?Sized> {
+    inner: RefMut<'a, T>,
+    _borrower: BorrowerGuard<'a>,
+}
+
+impl<'a, T: ?Sized> StateMut<'a, T> {
+    fn map<U: ?Sized>(self, f: impl FnOnce(&mut T) -> &mut U) -> StateMut<'a, U> {
+        StateMut {
+            inner: RefMut::map(self.inner, f),
+            _borrower: self._borrower,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for StateMut<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: ?Sized> DerefMut for StateMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+/// Forgets the mutable borrower of a module state once the borrow ends.
+struct BorrowerGuard<'a>(&'a Cell<Option<&'static str>>);
+
+impl Drop for BorrowerGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(None);
+    }
+}
+
+/// Casts of a module state to `T`, the module itself or an interface it provides, see [`Engine::provide`](crate::Engine::provide).
+pub(crate) struct Casts<T: ?Sized> {
+    read: Rc<ReadCast<T>>,
+    write: Rc<WriteCast<T>>,
+}
+
+type ReadCast<T> = dyn Fn(&dyn Any) -> &T;
+type WriteCast<T> = dyn Fn(&mut dyn Any) -> &mut T;
+
+impl<T: ?Sized> Clone for Casts<T> {
+    fn clone(&self) -> Self {
+        Self {
+            read: self.read.clone(),
+            write: self.write.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized + 'static> Casts<T> {
+    pub(crate) fn new<M: Module>(read: fn(&M) -> &T, write: fn(&mut M) -> &mut T) -> Self {
+        Self {
+            read: Rc::new(move |state| read(state.downcast_ref::<M>().unwrap())),
+            write: Rc::new(move |state| write(state.downcast_mut::<M>().unwrap())),
+        }
+    }
+}
+
+/// An immutable handle to a `Module`, or to an interface provided by a module (`Dependency<dyn Trait>`).
+///
+/// You can read it's state with the `read_state(&self)` method.
+pub struct Dependency<T: ?Sized + 'static> {
+    pub(crate) owner: Option<&'static str>,
+    pub(crate) state: ModuleState,
+    casts: Casts<T>,
+}
+
+impl<T: Module> Dependency<T> {
+    pub(crate) fn new(state: &ModuleState, owner: Option<&'static str>) -> Self {
+        Self::with_casts(state, owner, Casts::new::<T>(|state| state, |state| state))
+    }
+}
+
+impl<T: ?Sized + 'static> Dependency<T> {
+    pub(crate) fn with_casts(
+        state: &ModuleState,
+        owner: Option<&'static str>,
+        casts: Casts<T>,
+    ) -> Self {
+        Self {
+            owner,
+            state: state.clone(),
+            casts,
+        }
+    }
+
+    /// Read the module state immutably
+    ///
+    /// Panics if the state is currently borrowed mutably, see [`Self::try_read_state`].
+    pub fn read_state(&self) -> Ref<'_, T> {
+        self.try_read_state().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Read the module state immutably, or return which modules collided if it is currently borrowed mutably.
+    pub fn try_read_state(&self) -> Result<Ref<'_, T>, BorrowError> {
+        Ok(Ref::map(self.state.try_borrow(self.owner)?, |state| {
+            (self.casts.read)(&**state)
+        }))
+    }
+}
+
+/// A mutable handle to a `Module`, obtained with [`Engine::dependency_mut`](crate::Engine::dependency_mut).
+///
+/// Dereferences to a [`Dependency`] for reading, and can write to the state with the `write_state(&self)` method.
+pub struct DependencyMut<T: ?Sized + 'static> {
+    pub(crate) inner: Dependency<T>,
+}
+
+impl<T: ?Sized + 'static> DependencyMut<T> {
+    /// Write to the module state
+    ///
+    /// Panics if the state is currently borrowed, see [`Self::try_write_state`].
+    pub fn write_state(&self) -> StateMut<'_, T> {
+        self.try_write_state().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Write to the module state, or return which modules collided if it is currently borrowed.
+    pub fn try_write_state(&self) -> Result<StateMut<'_, T>, BorrowError> {
+        Ok(self
+            .inner
+            .state
+            .try_borrow_mut(self.inner.owner)?
+            .map(|state| (self.inner.casts.write)(&mut **state)))
+    }
+}
+
+impl<T: ?Sized + 'static> Deref for DependencyMut<T> {
+    type Target = Dependency<T>;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// A handle to a `Module` that may not be loaded, obtained with [`Engine::soft_dependency`](crate::Engine::soft_dependency).
+///
+/// Unlike a [`Dependency`], it doesn't load the module and doesn't prevent it from being unloaded:
+/// it gives access to the module whenever it is loaded, even if it was loaded after the handle was created.
+pub struct SoftDependency<T: Module> {
+    _marker: PhantomData<T>,
+    owner: Option<&'static str>,
+    slot: SoftSlot,
+}
+
+impl<T: Module> SoftDependency<T> {
+    pub(crate) fn new(slot: &SoftSlot, owner: Option<&'static str>) -> Self {
+        Self {
+            _marker: PhantomData,
+            owner,
+            slot: slot.clone(),
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.slot.borrow().strong_count() > 0
+    }
+
+    /// Returns the module as a `Dependency<T>` if it is currently loaded.
+    ///
+    /// The returned dependency prevents the module from being unloaded, so it should not be kept around.
+    pub fn get(&self) -> Option<Dependency<T>> {
+        let state = self.slot.borrow().upgrade()?;
+        Some(Dependency::new(&state, self.owner))
+    }
+}
@@ -1,67 +1,222 @@
 use std::{
     any::{Any, TypeId},
     collections::HashMap,
+    future::Future,
+    ops::Deref,
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::{
+    tasks::{task, Task},
+    timers::Delay,
+    Engine, Module, ModuleId, ModuleListener,
 };
 
-use crate::ModuleListener;
-#[cfg(debug_assertions)]
 pub(crate) trait DebugName {
-    fn of(&self) -> String;
+    fn type_name(&self) -> &'static str;
 }
 impl<T> DebugName for T {
-    fn of(&self) -> String {
-        let parts = std::any::type_name::<Self>()
-            .split("::")
-            .collect::<Vec<_>>();
-        format!(
-            "{} (inside of {})",
-            parts.last().unwrap(),
-            &parts[..parts.len() - 1]
-                .into_iter()
-                .map(|p| format!("::{}", p))
-                .collect::<Vec<_>>()
-                .concat()[2..]
-        )
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Formats `a::b::C` as `C (inside of a::b)`
+pub(crate) fn format_type_name(type_name: &str) -> String {
+    // Generic parameters are kept with the name, `a::B<c::D>` is `B<c::D> (inside of a)`
+    let generics = type_name.find('<').unwrap_or(type_name.len());
+    match type_name[..generics].rfind("::") {
+        Some(i) => format!("{} (inside of {})", &type_name[i + 2..], &type_name[..i]),
+        None => type_name.to_string(),
     }
 }
 #[allow(private_bounds)]
 pub trait Event: 'static + DebugName {
     fn as_any(self: Box<Self>) -> Box<dyn Any>;
+    fn as_any_ref(&self) -> &dyn Any;
 }
 impl<T: Any + DebugName> Event for T {
     fn as_any(self: Box<Self>) -> Box<dyn Any> {
         self
     }
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// An event dispatched to a single module, see [`EventQueue::push_to`]
+pub(crate) struct Targeted {
+    target: ModuleId,
+    event: Box<dyn Event>,
+}
+
+impl Targeted {
+    pub(crate) fn new<T: Event>(target: ModuleId, event: T) -> Self {
+        Self {
+            target,
+            event: Box::new(event),
+        }
+    }
 }
 
 /// Allows for module to listen to Event `T`.
 ///
-/// **WARNING: For this to work you need to add the event type to the associated type `<Self as Module>::ListeningTo`**
+/// **WARNING: For this to work you need to add the event type to the associated type `<Self as Module>::ListeningTo`
+/// or to register it in [`Module::listeners`](crate::Module::listeners), unless it is implemented in a [`listeners!`](crate::listeners) block**
 pub trait Listener<T: Event>: 'static {
     fn on_event(&mut self, event: &mut T, queue: &mut EventQueue);
+
+    /// Constraints on when this listener is dispatched relative to the other listeners of `T`.
+    ///
+    /// By default listeners are dispatched in the order their modules were loaded.
+    fn order() -> ListenerOrder
+    where
+        Self: Sized,
+    {
+        ListenerOrder::default()
+    }
+}
+
+/// Ordering constraints of a [`Listener`], resolved by the [`Engine`](crate::Engine) whenever a module is loaded.
+///
+/// `before` and `after` constraints are always respected, the priority only decides between listeners that are not constrained.
+#[derive(Clone, Default)]
+pub struct ListenerOrder {
+    pub(crate) priority: i32,
+    pub(crate) before: Vec<TypeId>,
+    pub(crate) after: Vec<TypeId>,
 }
 
-/// Queue of events to be dispatched
+impl ListenerOrder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Listeners with a higher priority are dispatched first, defaults to `0`.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Dispatch this listener before the one of module `M`, if `M` listens to the same event.
+    pub fn before<M: Module>(mut self) -> Self {
+        self.before.push(TypeId::of::<M>());
+        self
+    }
+
+    /// Dispatch this listener after the one of module `M`, if `M` listens to the same event.
+    pub fn after<M: Module>(mut self) -> Self {
+        self.after.push(TypeId::of::<M>());
+        self
+    }
+}
+
+/// A type-erased [`Listener`] of a module `T`, created by [`EventList`].
+pub struct RawListener<T> {
+    pub(crate) event_name: &'static str,
+    pub(crate) order: ListenerOrder,
+    pub(crate) callback: RawCallback<T>,
+    #[cfg(feature = "parallel")]
+    pub(crate) parallel: Option<crate::parallel::RawParallelCallback>,
+}
+
+pub(crate) type RawCallback<T> = Box<dyn Fn(&mut T, &mut dyn Any, &mut EventQueue)>;
+
+/// Registers the listeners of a module `T`, see [`Module::listeners`](crate::Module::listeners).
+pub struct Listeners<T> {
+    pub(crate) inner: ModuleListener<T>,
+    pub(crate) emits: Vec<&'static str>,
+}
+
+impl<T: 'static> Listeners<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: HashMap::new(),
+            emits: Vec::new(),
+        }
+    }
+
+    /// Declares that the module pushes the events `E`.
+    ///
+    /// This is only used to document the module, see [`Engine::event_graph_dot`](crate::Engine::event_graph_dot).
+    pub fn emits<E: Event>(&mut self) -> &mut Self {
+        let name = std::any::type_name::<E>();
+        if !self.emits.contains(&name) {
+            self.emits.push(name);
+        }
+        self
+    }
+
+    /// Dispatches the events `E` to the [`Listener<E>`] implementation of `T`.
+    pub fn listen<E: Event>(&mut self) -> &mut Self
+    where
+        T: Listener<E>,
+    {
+        let callback: RawCallback<T> = Box::new(|_self, any_event, event_queue| {
+            Listener::<E>::on_event(_self, any_event.downcast_mut().unwrap(), event_queue)
+        });
+        self.inner.insert(
+            TypeId::of::<E>(),
+            RawListener {
+                event_name: std::any::type_name::<E>(),
+                order: <T as Listener<E>>::order(),
+                callback,
+                #[cfg(feature = "parallel")]
+                parallel: None,
+            },
+        );
+        self
+    }
+
+    /// Dispatches the events `E` to the [`ParallelListener<E>`](crate::parallel::ParallelListener) implementation of `T`,
+    /// concurrently with the other parallel listeners of `E`.
+    #[cfg(feature = "parallel")]
+    pub fn listen_parallel<E: Event + Sync>(&mut self) -> &mut Self
+    where
+        T: crate::parallel::ParallelListener<E>,
+    {
+        self.inner
+            .insert(TypeId::of::<E>(), crate::parallel::raw_listener::<T, E>());
+        self
+    }
+}
+
+/// Queue of events to be dispatched.
+///
+/// It is also the dispatch context of the event currently being handled, see [`EventQueue::consume`].
 pub struct EventQueue {
     inner: Vec<Box<dyn Event>>,
+    pub(crate) commands: Vec<Command>,
+    pub(crate) deferred: Vec<(Delay, Box<dyn Event>)>,
+    pub(crate) tasks: Vec<Task>,
+    consumed: bool,
+    /// Module currently handling the event, the sender of the queries, see [`EventQueue::query`]
+    pub(crate) current: Option<ModuleId>,
 }
 
+pub(crate) type Command = Box<dyn FnOnce(&mut Engine)>;
+
 impl EventQueue {
     pub(crate) fn new() -> Self {
-        Self { inner: Vec::new() }
+        Self {
+            inner: Vec::new(),
+            commands: Vec::new(),
+            deferred: Vec::new(),
+            tasks: Vec::new(),
+            consumed: false,
+            current: None,
+        }
     }
 
-    pub(crate) fn take_last(&mut self) -> Option<Box<dyn Event>> {
-        let l = self.inner.len();
-        if l == 0 {
-            return None;
-        }
-        Some(self.inner.remove(l - 1))
+    /// Takes the pushed events, in push order
+    pub(crate) fn take_events(&mut self) -> Vec<Box<dyn Event>> {
+        std::mem::take(&mut self.inner)
     }
 
-    pub(crate) fn extend(&mut self, mut other: Self) {
-        other.inner.reverse();
-        self.inner.extend(other.inner)
+    #[cfg(feature = "parallel")]
+    pub(crate) fn extend_boxed(&mut self, events: impl IntoIterator<Item = Box<dyn Event>>) {
+        self.inner.extend(events)
     }
 
     pub fn is_empty(&mut self) -> bool {
@@ -72,6 +227,326 @@ impl EventQueue {
     pub fn push<T: Event>(&mut self, event: T) {
         self.inner.push(Box::new(event))
     }
+
+    /// Runs `command` with the engine once the current schedule is done, in push order,
+    /// even if the schedule exceeded its [`ScheduleBudget`](crate::ScheduleBudget).
+    ///
+    /// Meant for the rare listeners that need to load or unload modules, events should be preferred otherwise.
+    pub fn push_command(&mut self, command: impl FnOnce(&mut Engine) + 'static) {
+        self.commands.push(Box::new(command))
+    }
+
+    /// Pushes a new event `T` to be dispatched to the module `target` only, if it listens to it,
+    /// for example a single keyed instance (see [`Engine::dependency_with_key`]).
+    pub fn push_to<T: Event>(&mut self, target: ModuleId, event: T) {
+        self.inner.push(Box::new(Targeted::new(target, event)))
+    }
+
+    /// Pushes a new event `T` to be dispatched to the module `M` only, if it listens to it.
+    pub fn send_to<M: Module, T: Event>(&mut self, event: T) {
+        self.push_to(ModuleId::of::<M>(), event)
+    }
+
+    /// Pushes the request `R` wrapped in a [`Query<R>`], whose listeners can reply with a [`Request::Reply`]
+    /// dispatched back to the module handling the current event only.
+    pub fn query<R: Request>(&mut self, request: R) {
+        self.push(Query {
+            request,
+            sender: self.current,
+        })
+    }
+
+    /// Same as [`EventQueue::query`], but the query is only dispatched to the module `M`.
+    pub fn query_to<M: Module, R: Request>(&mut self, request: R) {
+        self.send_to::<M, _>(Query {
+            request,
+            sender: self.current,
+        })
+    }
+
+    /// Pushes a new event `T` to be dispatched on the next platform update.
+    pub fn push_next_update<T: Event>(&mut self, event: T) {
+        self.push_after_ticks(1, event)
+    }
+
+    /// Pushes a new event `T` to be dispatched on the first platform update once `delay` has elapsed.
+    pub fn push_after<T: Event>(&mut self, delay: Duration, event: T) {
+        self.deferred.push((Delay::after(delay), Box::new(event)))
+    }
+
+    /// Pushes a new event `T` to be dispatched after `ticks` platform updates (at least one).
+    pub fn push_after_ticks<T: Event>(&mut self, ticks: usize, event: T) {
+        self.deferred.push((Delay::Updates(ticks), Box::new(event)))
+    }
+
+    /// Spawns `future` on the [`EngineExecutor`](crate::tasks::EngineExecutor), its output is dispatched as an event
+    /// on a platform update once it completes.
+    ///
+    /// ```ignore
+    /// queue.spawn(async { FileRead(unblock(|| std::fs::read("save.bin")).await) });
+    /// ```
+    pub fn spawn<E: Event>(&mut self, future: impl Future<Output = E> + 'static) {
+        self.tasks.push(task(future))
+    }
+
+    /// Marks the event currently being handled as consumed,
+    /// the listeners dispatched after this one won't receive it.
+    ///
+    /// Listeners are dispatched in the order resolved from their [`ListenerOrder`],
+    /// so a module swallowing events should be ordered before the ones it hides them from.
+    pub fn consume(&mut self) {
+        self.consumed = true;
+    }
+
+    /// Check if the event currently being handled has been consumed by a previous listener
+    pub fn is_consumed(&self) -> bool {
+        self.consumed
+    }
+}
+
+/// An event asking for a reply, see [`EventQueue::query`].
+pub trait Request: Event {
+    type Reply: Event;
+}
+
+/// A [`Request`] pushed with [`EventQueue::query`], listened to by the modules able to reply to it.
+///
+/// ```ignore
+/// impl Listener<Query<IsLoaded>> for AssetsModule {
+///     fn on_event(&mut self, query: &mut Query<IsLoaded>, queue: &mut EventQueue) {
+///         query.reply(queue, Loaded(self.is_loaded(&query.0)));
+///     }
+/// }
+/// ```
+pub struct Query<R: Request> {
+    request: R,
+    sender: Option<ModuleId>,
+}
+
+impl<R: Request> Query<R> {
+    /// A query without sender, to be passed to [`Engine::run_with`], its replies are dispatched to all of their listeners.
+    pub fn new(request: R) -> Self {
+        Self {
+            request,
+            sender: None,
+        }
+    }
+
+    /// Module that pushed the query, `None` if it was pushed from outside of any listener
+    pub fn sender(&self) -> Option<ModuleId> {
+        self.sender
+    }
+
+    /// Pushes `reply` to be dispatched to the sender of the query only,
+    /// or to all of its listeners if the query has no sender.
+    pub fn reply(&self, queue: &mut EventQueue, reply: R::Reply) {
+        match self.sender {
+            Some(sender) => queue.push_to(sender, reply),
+            None => queue.push(reply),
+        }
+    }
+
+    pub fn into_request(self) -> R {
+        self.request
+    }
+}
+
+impl<R: Request> Deref for Query<R> {
+    type Target = R;
+    fn deref(&self) -> &Self::Target {
+        &self.request
+    }
+}
+
+/// Order in which the events pushed while handling an event are dispatched, relative to the events already scheduled.
+///
+/// In both cases, the events pushed by a listener are dispatched in the order they were pushed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DispatchOrder {
+    /// Pushed events are dispatched right after the event that pushed them, before any event already scheduled.
+    ///
+    /// For example `GraphicsModule` relies on it so that everything pushed while handling its `PreSubmitRenderEvent`
+    /// is dispatched before its `SubmitRenderEvent`.
+    #[default]
+    DepthFirst,
+    /// Pushed events are dispatched once every event already scheduled has been dispatched.
+    BreadthFirst,
+}
+
+/// Selects the [`DispatchOrder`] used by the [`Engine`](crate::Engine), either globally or per event type.
+///
+/// The order applies to the events pushed while handling an event of a given type,
+/// defaults to [`DispatchOrder::DepthFirst`] for all events.
+#[derive(Clone, Debug, Default)]
+pub struct DispatchPolicy {
+    default: DispatchOrder,
+    overrides: HashMap<TypeId, DispatchOrder>,
+}
+
+impl DispatchPolicy {
+    pub fn new(default: DispatchOrder) -> Self {
+        Self {
+            default,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Uses `order` for the events pushed while handling an event `T`.
+    pub fn with<T: Event>(mut self, order: DispatchOrder) -> Self {
+        self.overrides.insert(TypeId::of::<T>(), order);
+        self
+    }
+
+    pub fn order_of(&self, event: TypeId) -> DispatchOrder {
+        self.overrides.get(&event).copied().unwrap_or(self.default)
+    }
+}
+
+/// Limits on the events dispatched by a single call to [`Engine::run_with`](crate::Engine::run_with),
+/// turning runaway event loops (a listener pushing the event it listens to, two listeners ping-ponging...) into errors.
+///
+/// By default only the depth is limited, to 256 nested events.
+#[derive(Clone, Debug)]
+pub struct ScheduleBudget {
+    /// Maximum number of events dispatched in the schedule
+    pub max_dispatches: Option<usize>,
+    /// Maximum number of ancestors of a dispatched event, the event passed to `run_with` having none
+    pub max_depth: Option<usize>,
+    /// Once elapsed, the events left in the schedule are deferred to the next platform update
+    /// instead of being dispatched, see [`EventQueue::push_next_update`].
+    pub time_budget: Option<Duration>,
+}
+
+impl Default for ScheduleBudget {
+    fn default() -> Self {
+        Self {
+            max_dispatches: None,
+            max_depth: Some(256),
+            time_budget: None,
+        }
+    }
+}
+
+impl ScheduleBudget {
+    /// A budget without any limit
+    pub fn unlimited() -> Self {
+        Self {
+            max_dispatches: None,
+            max_depth: None,
+            time_budget: None,
+        }
+    }
+
+    pub fn max_dispatches(mut self, max_dispatches: usize) -> Self {
+        self.max_dispatches = Some(max_dispatches);
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+}
+
+/// An event waiting to be dispatched in a schedule, along with the chain of events that pushed it.
+pub(crate) struct Scheduled {
+    pub(crate) event: Box<dyn Event>,
+    /// Module the event is dispatched to, all of its listeners if `None`
+    pub(crate) target: Option<ModuleId>,
+    pub(crate) trace: Rc<EventTrace>,
+}
+
+pub(crate) struct EventTrace {
+    pub(crate) event: &'static str,
+    pub(crate) depth: usize,
+    parent: Option<Rc<EventTrace>>,
+}
+
+impl Scheduled {
+    pub(crate) fn root(event: Box<dyn Event>) -> Self {
+        Self::new(event, None)
+    }
+
+    pub(crate) fn child(event: Box<dyn Event>, parent: &Rc<EventTrace>) -> Self {
+        Self::new(event, Some(parent))
+    }
+
+    fn new(event: Box<dyn Event>, parent: Option<&Rc<EventTrace>>) -> Self {
+        let (event, target) = match (*event).as_any_ref().is::<Targeted>() {
+            true => {
+                let targeted = event.as_any().downcast::<Targeted>().unwrap();
+                (targeted.event, Some(targeted.target))
+            }
+            false => (event, None),
+        };
+        let trace = Rc::new(EventTrace {
+            event: (*event).type_name(),
+            depth: parent.map_or(0, |parent| parent.depth + 1),
+            parent: parent.cloned(),
+        });
+        Self {
+            event,
+            target,
+            trace,
+        }
+    }
+
+    /// The event to be scheduled again, still targeted
+    pub(crate) fn into_event(self) -> Box<dyn Event> {
+        match self.target {
+            Some(target) => Box::new(Targeted {
+                target,
+                event: self.event,
+            }),
+            None => self.event,
+        }
+    }
+}
+
+impl EventTrace {
+    /// Names of the events from the root of the schedule to this one,
+    /// consecutive repetitions of the same event are collapsed.
+    pub(crate) fn chain(&self) -> Vec<String> {
+        let mut events = vec![self.event];
+        let mut parent = self.parent.as_deref();
+        while let Some(trace) = parent {
+            events.push(trace.event);
+            parent = trace.parent.as_deref();
+        }
+        events.reverse();
+
+        let mut chain: Vec<(&'static str, usize)> = Vec::new();
+        for event in events {
+            match chain.last_mut() {
+                Some((last, count)) if *last == event => *count += 1,
+                _ => chain.push((event, 1)),
+            }
+        }
+        chain
+            .into_iter()
+            .map(|(event, count)| match count {
+                1 => format_type_name(event),
+                n => format!("{} x{}", format_type_name(event), n),
+            })
+            .collect()
+    }
+}
+
+impl Drop for EventTrace {
+    /// Unlinks the chain iteratively, it can be as deep as the schedule budget allows
+    fn drop(&mut self) {
+        let mut parent = self.parent.take();
+        while let Some(trace) = parent {
+            parent = Rc::try_unwrap(trace)
+                .ok()
+                .and_then(|mut trace| trace.parent.take());
+        }
+    }
 }
 
 /// Simply a tuple of Events, for examples: `()`, `(EventA,)` or `(EventA, EventB, EventC)`.
@@ -79,6 +554,8 @@ impl EventQueue {
 ///
 /// In other terms:
 /// `(A, .., Z,): EventList<T>` is valid only if `T: Listener<A> + .. + Listener<Z>`
+///
+/// Tuples are limited to 16 events, modules listening to more of them can register the others in [`Module::listeners`](crate::Module::listeners).
 pub trait EventList<T> {
     fn raw_listeners() -> ModuleListener<T>;
 }
@@ -92,17 +569,82 @@ macro_rules! _impl {
     ($($name:tt)*) => {
         impl<T, $($name: Event),*> EventList<T> for ($($name,)*) where T: 'static $( + Listener<$name>)* {
             fn raw_listeners() -> ModuleListener<T> {
-                let mut map = HashMap::new();
-                $(
-                    let callback:  Box<dyn Fn(&mut T, &mut dyn Any, &mut EventQueue)> = Box::new(|_self, any_event, event_queue| {
-                        Listener::<$name>::on_event(_self, any_event.downcast_mut().unwrap(), event_queue)
-                    });
-                    map.insert(TypeId::of::<$name>(), callback);
-                )*
-                map
+                let mut listeners = Listeners::new();
+                $(listeners.listen::<$name>();)*
+                listeners.inner
             }
         }
     };
 }
 
 #[rustfmt::skip] mod _impl16 { use super::*; _impl!(A);_impl!(A B); _impl!(A B C);_impl!(A B C D);_impl!(A B C D E);_impl!(A B C D E F);_impl!(A B C D E F G);_impl!(A B C D E F G H);_impl!(A B C D E F G H I);_impl!(A B C D E F G H I J);_impl!(A B C D E F G H I J K);_impl!(A B C D E F G H I J K L);_impl!(A B C D E F G H I J K L M);_impl!(A B C D E F G H I J K L M N);_impl!(A B C D E F G H I J K L M N O);_impl!(A B C D E F G H I J K L M N O P);}
+
+/// Listeners of a module, implemented in a [`listeners!`](crate::listeners) block.
+pub trait ImplementedListeners: Sized + 'static {
+    fn listen_implemented(listeners: &mut Listeners<Self>);
+}
+
+/// Every [`Listener`] implemented by the module in its [`listeners!`](crate::listeners) block,
+/// used as `type ListeningTo = Implemented;`.
+///
+/// Unlike tuples, the number of events is not limited.
+pub struct Implemented;
+
+impl<T: ImplementedListeners> EventList<T> for Implemented {
+    fn raw_listeners() -> ModuleListener<T> {
+        let mut listeners = Listeners::new();
+        T::listen_implemented(&mut listeners);
+        listeners.inner
+    }
+}
+
+/// Implements the [`Listener`]s of a module and registers all of them, so that none can be left out of `ListeningTo`.
+///
+/// The module uses them with `type ListeningTo = Implemented;`, see [`Implemented`].
+///
+/// A block holds the listeners of a single module, an impl for another module fails to compile,
+/// and each module can only have one block.
+/// Generic impls (`impl<T> Listener<E> for Module<T>`) aren't accepted either,
+/// generic modules list their events in `ListeningTo` or [`Module::listeners`](crate::Module::listeners) instead.
+///
+/// ```ignore
+/// impl Module for Game {
+///     type ListeningTo = Implemented;
+///     ...
+/// }
+///
+/// listeners! {
+///     impl Listener<StartEvent> for Game {
+///         fn on_event(&mut self, _: &mut StartEvent, _: &mut EventQueue) {}
+///     }
+///
+///     impl Listener<StopEvent> for Game {
+///         fn on_event(&mut self, _: &mut StopEvent, _: &mut EventQueue) {}
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! listeners {
+    (
+        $(#[$meta:meta])*
+        impl Listener<$event:ty> for $module:ty { $($body:tt)* }
+        $(
+            $(#[$metas:meta])*
+            impl Listener<$events:ty> for $modules:ty { $($bodies:tt)* }
+        )*
+    ) => {
+        $(#[$meta])*
+        impl $crate::events::Listener<$event> for $module { $($body)* }
+        $(
+            $(#[$metas])*
+            impl $crate::events::Listener<$events> for $modules { $($bodies)* }
+        )*
+
+        impl $crate::events::ImplementedListeners for $module {
+            fn listen_implemented(listeners: &mut $crate::events::Listeners<Self>) {
+                listeners.listen::<$event>();
+                $(listeners.listen::<$events>();)*
+            }
+        }
+    };
+}
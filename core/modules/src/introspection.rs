@@ -0,0 +1,272 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::{Debug, Display},
+    time::Duration,
+};
+
+use crate::{events::format_type_name, Engine, Module, ModuleId};
+
+/// A module exposing parts of its state for debugging, see [`Engine::modules`].
+///
+/// **WARNING: For this to work the module needs to be registered with [`Engine::enable_debug`],
+/// for example from its `Module::new`**
+pub trait ModuleDebug: Module {
+    fn debug(&self, fields: &mut DebugFields);
+}
+
+/// Key/value pairs describing the state of a module, filled by [`ModuleDebug::debug`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DebugFields {
+    fields: Vec<(String, String)>,
+}
+
+impl DebugFields {
+    pub fn field(&mut self, key: impl Into<String>, value: impl Debug) -> &mut Self {
+        self.fields.push((key.into(), format!("{:?}", value)));
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+pub(crate) type Debugger = fn(&dyn Any, &mut DebugFields);
+
+/// Metadata of a loaded module, returned by [`Engine::modules`].
+#[derive(Clone, Debug)]
+pub struct ModuleInfo {
+    pub id: ModuleId,
+    /// Position of the module in the load order
+    pub load_index: usize,
+    /// Time spent in `Module::new`, including the loading of its dependencies
+    pub init_time: Duration,
+    /// Type names of the events the module listens to
+    pub events: Vec<&'static str>,
+    /// Type names of the events the module declared to push, see [`Listeners::emits`](crate::events::Listeners::emits)
+    pub emits: Vec<&'static str>,
+    /// Modules requested by the module when it was initialized
+    pub dependencies: Vec<ModuleId>,
+    /// Modules that requested this module when they were initialized
+    pub dependents: Vec<ModuleId>,
+    /// State exposed by the module if it implements [`ModuleDebug`],
+    /// `None` if it doesn't or if its state is currently borrowed mutably
+    pub state: Option<DebugFields>,
+}
+
+impl Display for ModuleInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| format_type_name(name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let modules = |ids: &[ModuleId]| {
+            ids.iter()
+                .map(ModuleId::format)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        writeln!(
+            f,
+            "{}. {} [init {:?}]",
+            self.load_index,
+            self.id.format(),
+            self.init_time
+        )?;
+        if !self.events.is_empty() {
+            writeln!(f, "    listens to: {}", list(&self.events))?;
+        }
+        if !self.emits.is_empty() {
+            writeln!(f, "    emits: {}", list(&self.emits))?;
+        }
+        if !self.dependencies.is_empty() {
+            writeln!(f, "    depends on: {}", modules(&self.dependencies))?;
+        }
+        if !self.dependents.is_empty() {
+            writeln!(f, "    depended on by: {}", modules(&self.dependents))?;
+        }
+        for (key, value) in self.state.iter().flat_map(DebugFields::iter) {
+            writeln!(f, "    {} = {}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Engine {
+    /// Exposes the state of the module `T` in [`Engine::modules`], see [`ModuleDebug`].
+    ///
+    /// The registration is shared by the instances of `T`, and dropped when the last one is unloaded.
+    pub fn enable_debug<T: ModuleDebug>(&mut self) {
+        self.debuggers.insert(TypeId::of::<T>(), |state, fields| {
+            state.downcast_ref::<T>().unwrap().debug(fields)
+        });
+    }
+
+    /// Metadata of every loaded module, in load order.
+    pub fn modules(&self) -> Vec<ModuleInfo> {
+        self.load_order
+            .iter()
+            .enumerate()
+            .map(|(load_index, id)| {
+                let module = &self.modules[id];
+                let mut events = module
+                    .listeners
+                    .values()
+                    .map(|listener| listener.event_name)
+                    .collect::<Vec<_>>();
+                events.sort();
+
+                ModuleInfo {
+                    id: *id,
+                    load_index,
+                    init_time: module.init_time,
+                    events,
+                    emits: module.emits.clone(),
+                    dependencies: module
+                        .dependencies
+                        .iter()
+                        .filter(|dependency| self.modules.contains_key(dependency))
+                        .copied()
+                        .collect(),
+                    dependents: self
+                        .load_order
+                        .iter()
+                        .filter(|other| self.modules[other].dependencies.contains(id))
+                        .copied()
+                        .collect(),
+                    state: self.debuggers.get(&id.type_id).and_then(|debugger| {
+                        let state = module.state.try_borrow(None).ok()?;
+                        let mut fields = DebugFields::default();
+                        debugger(&**state, &mut fields);
+                        Some(fields)
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    /// Human readable description of the loaded modules, see [`Engine::modules`].
+    pub fn dump_modules(&self) -> String {
+        let modules = self.modules();
+        let mut dump = format!("{} loaded module(s):\n", modules.len());
+        for module in modules {
+            dump += &module.to_string();
+        }
+        dump
+    }
+
+    /// Graphviz DOT description of the modules and their dependencies, in load order.
+    ///
+    /// Edges go from a module to the modules it requested when it was initialized.
+    pub fn module_graph_dot(&self) -> String {
+        let modules = self.modules();
+        let mut dot = String::from("digraph modules {\n");
+        for module in &modules {
+            dot += &dot_node(&module.id.to_string(), &module.id.format(), "box");
+        }
+        for module in &modules {
+            for dependency in &module.dependencies {
+                dot += &format!(
+                    "    {:?} -> {:?};\n",
+                    module.id.to_string(),
+                    dependency.to_string()
+                );
+            }
+        }
+        dot + "}\n"
+    }
+
+    /// Graphviz DOT description of the modules and the events they listen to or declared to push.
+    ///
+    /// Edges go from the modules pushing an event to the event, and from the event to its listeners.
+    pub fn event_graph_dot(&self) -> String {
+        let modules = self.modules();
+        let mut events = modules
+            .iter()
+            .flat_map(|module| module.events.iter().chain(&module.emits))
+            .collect::<Vec<_>>();
+        events.sort();
+        events.dedup();
+
+        let mut dot = String::from("digraph events {\n");
+        for module in &modules {
+            dot += &dot_node(&module.id.to_string(), &module.id.format(), "box");
+        }
+        for event in events {
+            dot += &dot_node(event, &format_type_name(event), "ellipse");
+        }
+        for module in &modules {
+            let id = module.id.to_string();
+            for event in &module.emits {
+                dot += &format!("    {:?} -> {:?};\n", id, event);
+            }
+            for event in &module.events {
+                dot += &format!("    {:?} -> {:?};\n", event, id);
+            }
+        }
+        dot + "}\n"
+    }
+
+    /// JSON description of the modules, their dependencies and the events they listen to or declared to push,
+    /// in load order.
+    ///
+    /// ```json
+    /// { "modules": [{ "name": "a::Module", "key": null, "dependencies": [], "events": ["a::Event"], "emits": [] }] }
+    /// ```
+    ///
+    /// Keyed instances are referred to as `"a::Module[key]"` in the dependencies.
+    pub fn module_graph_json(&self) -> String {
+        let list = |names: &[&str]| {
+            let names = names
+                .iter()
+                .map(|name| json_string(name))
+                .collect::<Vec<_>>();
+            format!("[{}]", names.join(", "))
+        };
+        let modules = self
+            .modules()
+            .iter()
+            .map(|module| {
+                let dependencies = module
+                    .dependencies
+                    .iter()
+                    .map(ModuleId::to_string)
+                    .collect::<Vec<_>>();
+                format!(
+                    "    {{ \"name\": {}, \"key\": {}, \"dependencies\": {}, \"events\": {}, \"emits\": {} }}",
+                    json_string(module.id.name()),
+                    module.id.key().map_or("null".to_owned(), json_string),
+                    list(&dependencies.iter().map(String::as_str).collect::<Vec<_>>()),
+                    list(&module.events),
+                    list(&module.emits)
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("{{ \"modules\": [\n{}\n] }}\n", modules.join(",\n"))
+    }
+}
+
+fn dot_node(id: &str, label: &str, shape: &str) -> String {
+    format!("    {:?} [label={:?}, shape={}];\n", id, label, shape)
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut json = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => json += "\\\"",
+            '\\' => json += "\\\\",
+            c if c.is_control() => json += &format!("\\u{:04x}", c as u32),
+            c => json.push(c),
+        }
+    }
+    json + "\""
+}
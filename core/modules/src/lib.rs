@@ -13,33 +13,59 @@
 //!
 //! # Optional features
 //! - `standards`: often used events (game engine related), useful for compatibility between modules (enabled by default)
+//! - `parallel`: dispatches [`ParallelListener`](parallel::ParallelListener)s concurrently on a thread pool
+//! - `snapshot`: captures and restores the state of [`SnapshotModule`](snapshot::SnapshotModule)s
+//! - `replay`: records the events passed to the engine and replays them, see [`EventRecorder`](replay::EventRecorder) (enables `snapshot`)
+//! - `config`: typed per-module configuration read from a TOML or RON file, see [`ModuleConfig`](config::ModuleConfig)
 
 use std::{
-    any::{Any, TypeId},
-    cell::{Ref, RefCell, RefMut},
-    collections::HashMap,
+    any::{type_name, Any, TypeId},
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap, VecDeque},
     error::Error,
     fmt::Display,
-    marker::PhantomData,
-    rc::Rc,
+    rc::{Rc, Weak},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
 };
 
 use events::Event;
 #[cfg(feature = "debuglog")]
 use rgine_logger::debug;
-use rgine_logger::init_logger;
+use rgine_logger::{info, init_logger};
 
-use crate::events::{EventList, EventQueue};
+use crate::{
+    dependency::{Casts, ModuleCell, ModuleState, SoftSlot},
+    events::{
+        DispatchOrder, DispatchPolicy, EventList, EventQueue, Listeners, RawListener,
+        ScheduleBudget, Scheduled, Targeted,
+    },
+    timers::{Delay, Timers},
+};
 
+#[cfg(feature = "config")]
+pub mod config;
+mod dependency;
 pub mod events;
+pub mod introspection;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod profiler;
+#[cfg(feature = "replay")]
+pub mod replay;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
 #[cfg(feature = "standards")]
 pub mod standards;
+pub mod tasks;
+mod timers;
 pub mod utils;
 
 pub mod prelude {
     pub use crate::{
-        events::{EventQueue, Listener},
-        AnyResult, Dependency, Engine, Module,
+        events::{EventQueue, Implemented, Listener, ListenerOrder, Listeners, Query, Request},
+        listeners, AnyResult, Dependency, DependencyMut, Engine, Module, SoftDependency,
     };
 
     #[cfg(feature = "standards")]
@@ -50,135 +76,813 @@ pub mod prelude {
 /// Those can be loaded from the `Engine` struct.
 ///
 /// - Self is the Module `State`
-/// - ListeningTo is a list of events that the module is listening to `(EventA, .., EventZ,)` using the `Listener<SomeEvent>` trait
+/// - ListeningTo is a list of events that the module is listening to `(EventA, .., EventZ,)` using the `Listener<SomeEvent>` trait,
+///   more can be registered with [`Module::listeners`], or every listener implemented in a [`listeners!`] block with [`Implemented`](events::Implemented)
 ///
-/// TODO:
-///  - Allow for debug informations on a per module basis.
-///  - Maybe allow for hot reloading if possible
+/// Modules can expose debug informations with [`ModuleDebug`](introspection::ModuleDebug),
+/// and can be hot reloaded from dynamic libraries using the `rgine_hotreload` crate.
 pub trait Module: Any + Sized {
     type ListeningTo: EventList<Self>;
 
     fn new(ctx: &mut Engine) -> AnyResult<Self>;
+
+    /// Registers listeners in addition to the ones of `ListeningTo`, without limit on their number.
+    ///
+    /// ```ignore
+    /// fn listeners(listeners: &mut Listeners<Self>) {
+    ///     listeners.listen::<EventA>().listen::<EventB>();
+    /// }
+    /// ```
+    fn listeners(_: &mut Listeners<Self>) {}
+
+    /// Called once the module has been inserted into the engine, after its dependencies.
+    fn on_load(&mut self) {}
+
+    /// Called right before the module is removed from the engine, either by [`Engine::unload_module`]
+    /// or when the engine is dropped, in which case modules are unloaded in the reverse order of their loading.
+    ///
+    /// This is the place to release resources that depend on other modules (GPU surfaces, files...).
+    fn on_unload(&mut self) {}
 }
 
 #[derive(Debug)]
 /// Error relative to modules
 pub enum ModuleError {
-    /// Error occured during initialization
-    InitError(Box<dyn Error>),
-    /// Error occured because the engine can't support two instances of the same module
+    /// Error occured during the initialization of a module
+    InitError {
+        /// Type name of the module that failed to initialize
+        module: &'static str,
+        /// Type names of the modules being loaded when it failed, from the first requested to `module`
+        path: Vec<&'static str>,
+        source: Box<dyn Error>,
+    },
+    /// Error occured because a module with this id (type and key, see [`ModuleId`]) is already loaded
     AlreadyExist,
     /// Error occured because the target module could not be found
     NotFound,
     /// Error occured because the target module is in use and thus can't be unloaded
     InUse,
+    /// Error occured because the ordering constraints of the listeners of an event are circular
+    OrderingCycle {
+        event: &'static str,
+        modules: Vec<&'static str>,
+    },
+    /// Error occured because a module depends on itself, directly or through other modules.
+    /// Contains the chain of module type names, starting and ending with the module requested again
+    CircularDependency(Vec<&'static str>),
+    /// Error occured while loading the configuration, see [`Engine::try_new`]
+    #[cfg(feature = "config")]
+    Config(config::ConfigError),
 }
 
 impl Display for ModuleError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::InitError(_) => write!(f, "Failed to initialize module"),
-            Self::AlreadyExist => write!(
-                f,
-                "The engine can't support two instances of the same module"
-            ),
+            Self::InitError { module, path, .. } => {
+                write!(
+                    f,
+                    "Failed to initialize {}",
+                    events::format_type_name(module)
+                )?;
+                if path.len() > 1 {
+                    write!(f, ", loaded through: {}", format_chain(path))?;
+                }
+                Ok(())
+            }
+            Self::AlreadyExist => write!(f, "A module with this id/key is already loaded"),
             Self::NotFound => write!(f, "The target module could not be found"),
             Self::InUse => write!(f, "The target module is in use and thus can't be unloaded"),
+            Self::OrderingCycle { event, modules } => write!(
+                f,
+                "The listeners of {} have circular ordering constraints between: {}",
+                event,
+                modules.join(", ")
+            ),
+            Self::CircularDependency(chain) => {
+                write!(f, "Circular module dependency: {}", format_chain(chain))
+            }
+            #[cfg(feature = "config")]
+            Self::Config(_) => write!(f, "Failed to load the configuration"),
         }
     }
 }
 
-impl Error for ModuleError {}
+impl Error for ModuleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InitError { source, .. } => Some(&**source),
+            #[cfg(feature = "config")]
+            Self::Config(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+fn format_chain(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| events::format_type_name(name))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// Formats an error followed by its sources, for panic messages
+fn format_error(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        message += &format!("\n  caused by: {}", error);
+        source = error.source();
+    }
+    message
+}
+
+#[derive(Debug)]
+/// Error relative to the dispatch of events, see [`ScheduleBudget`](events::ScheduleBudget)
+pub enum ScheduleError {
+    /// Error occured because the schedule dispatched more events than allowed
+    TooManyDispatches { limit: usize, chain: Vec<String> },
+    /// Error occured because an event was nested deeper than allowed
+    TooDeep { limit: usize, chain: Vec<String> },
+}
+
+impl Display for ScheduleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyDispatches { limit, chain } => write!(
+                f,
+                "The schedule exceeded its budget of {} dispatches, while dispatching: {}",
+                limit,
+                chain.join(" -> ")
+            ),
+            Self::TooDeep { limit, chain } => write!(
+                f,
+                "The schedule exceeded its maximum depth of {} nested events, while dispatching: {}",
+                limit,
+                chain.join(" -> ")
+            ),
+        }
+    }
+}
+
+impl Error for ScheduleError {}
+
+pub use dependency::{BorrowError, Dependency, DependencyMut, SoftDependency, StateMut};
 
 /// A result with any error
 pub type AnyResult<T> = Result<T, Box<dyn Error>>;
 
-type Modules = HashMap<TypeId, AnyModule>;
-type EventModuleSubscribers = HashMap<TypeId, Vec<TypeId>>;
+/// Identifies a loaded module by its type and, for keyed instances, its key, see [`Engine::dependency_with_key`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ModuleId {
+    type_id: TypeId,
+    name: &'static str,
+    key: Option<&'static str>,
+}
+
+impl ModuleId {
+    /// The unkeyed instance of the module `T`, the one loaded by [`Engine::dependency`]
+    pub fn of<T: Module>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+            key: None,
+        }
+    }
+
+    /// The instance of the module `T` loaded by [`Engine::dependency_with_key`] with `key`
+    pub fn keyed<T: Module>(key: &str) -> Self {
+        Self {
+            key: Some(intern(key)),
+            ..Self::of::<T>()
+        }
+    }
+
+    /// Type name of the module
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn key(&self) -> Option<&'static str> {
+        self.key
+    }
+
+    /// Formats the module as `C (inside of a::b)`, followed by its key if any
+    pub(crate) fn format(&self) -> String {
+        match self.key {
+            Some(key) => format!("{} [{}]", events::format_type_name(self.name), key),
+            None => events::format_type_name(self.name),
+        }
+    }
+}
+
+/// Returns a `'static` copy of `key`, each distinct key being leaked once
+fn intern(key: &str) -> &'static str {
+    static KEYS: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut keys = KEYS.lock().unwrap_or_else(PoisonError::into_inner);
+    match keys.get(key) {
+        Some(key) => key,
+        None => {
+            let key: &'static str = Box::leak(key.into());
+            keys.insert(key);
+            key
+        }
+    }
+}
+
+impl Display for ModuleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.key {
+            Some(key) => write!(f, "{}[{}]", self.name, key),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+type Modules = HashMap<ModuleId, AnyModule>;
+type EventModuleSubscribers = HashMap<TypeId, Vec<ModuleId>>;
 
 /// Allows for instantiation, storage and event dispatching of modules
 pub struct Engine {
     modules: Modules,
+    load_order: Vec<ModuleId>,
     subscribers: EventModuleSubscribers,
+    /// Modules being initialized, the last one being the one currently calling [`Engine::dependency`]
+    loading: Vec<Loading>,
+    timers: Timers,
+    executor: tasks::EngineExecutor,
+    dispatch_policy: DispatchPolicy,
+    schedule_budget: ScheduleBudget,
+    debuggers: HashMap<TypeId, introspection::Debugger>,
+    /// Slots of the modules requested with [`Engine::soft_dependency`]
+    soft_slots: HashMap<ModuleId, SoftSlot>,
+    /// Providers of the interfaces registered with [`Engine::provide`], keyed by interface type
+    providers: HashMap<TypeId, Provider>,
+    profiler: Option<profiler::Profiler>,
+    #[cfg(feature = "config")]
+    config: config::Config,
+    #[cfg(feature = "snapshot")]
+    snapshots: HashMap<TypeId, snapshot::SnapshotHooks>,
+    #[cfg(feature = "replay")]
+    recorder: Option<replay::EventRecorder>,
 }
 
 impl Engine {
+    /// Creates an engine from its entrypoint module, after initializing the logger.
+    ///
+    /// If the program was started with the `--dump-modules` argument, the loaded modules are logged, see [`Engine::dump_modules`].
+    ///
+    /// Panics if the entrypoint module or one of its dependencies fails to load, see [`Engine::try_new`].
     pub fn new<Entrypoint: Module>() -> Self {
+        Self::try_new::<Entrypoint>().unwrap_or_else(|e| {
+            panic!(
+                "Failed to load engine entrypoint module: {}",
+                format_error(&e)
+            )
+        })
+    }
+
+    /// Creates an engine from its entrypoint module, after initializing the logger.
+    ///
+    /// With the `config` feature, the configuration is loaded from the usual sources first,
+    /// see [`Config::from_default_sources`](config::Config::from_default_sources).
+    ///
+    /// Returns an error if the entrypoint module or one of its dependencies fails to load.
+    pub fn try_new<Entrypoint: Module>() -> Result<Self, ModuleError> {
         init_logger();
-        Self::new_without_logger::<Entrypoint>()
+        #[cfg(feature = "config")]
+        let engine = Self::try_with_config::<Entrypoint>(
+            config::Config::from_default_sources().map_err(ModuleError::Config)?,
+        )?;
+        #[cfg(not(feature = "config"))]
+        let engine = Self::try_new_without_logger::<Entrypoint>()?;
+        if std::env::args_os().any(|arg| arg == "--dump-modules") {
+            info!("{}", engine.dump_modules().trim_end());
+        }
+        Ok(engine)
     }
 
     pub fn new_without_logger<Entrypoint: Module>() -> Self {
-        let mut _self = Self {
+        Self::try_new_without_logger::<Entrypoint>().unwrap_or_else(|e| {
+            panic!(
+                "Failed to load engine entrypoint module: {}",
+                format_error(&e)
+            )
+        })
+    }
+
+    /// Creates an engine from its entrypoint module, with an empty configuration if the `config` feature is enabled.
+    pub fn try_new_without_logger<Entrypoint: Module>() -> Result<Self, ModuleError> {
+        let mut _self = Self::empty();
+        _self.dependency::<Entrypoint>()?;
+        Ok(_self)
+    }
+
+    /// Creates an engine from its entrypoint module and the configuration read by [`Engine::config`], without initializing the logger.
+    #[cfg(feature = "config")]
+    pub fn try_with_config<Entrypoint: Module>(
+        config: config::Config,
+    ) -> Result<Self, ModuleError> {
+        let mut _self = Self::empty();
+        _self.config = config;
+        _self.dependency::<Entrypoint>()?;
+        Ok(_self)
+    }
+
+    fn empty() -> Self {
+        Self {
             modules: Modules::new(),
+            load_order: Vec::new(),
             subscribers: EventModuleSubscribers::new(),
-        };
-        _self
-            .dependency::<Entrypoint>()
-            .expect("Failed to load engine entrypoint module");
-        _self
+            loading: Vec::new(),
+            timers: Timers::default(),
+            executor: tasks::EngineExecutor::default(),
+            dispatch_policy: DispatchPolicy::default(),
+            schedule_budget: ScheduleBudget::default(),
+            debuggers: HashMap::new(),
+            soft_slots: HashMap::new(),
+            providers: HashMap::new(),
+            profiler: None,
+            #[cfg(feature = "config")]
+            config: config::Config::default(),
+            #[cfg(feature = "snapshot")]
+            snapshots: HashMap::new(),
+            #[cfg(feature = "replay")]
+            recorder: None,
+        }
     }
 
     /// Returns the module `T` as a `Dependency<T>`, loading it if not found.
     ///
     /// In case the initialization fail, an error is returned instead.
     pub fn dependency<T: Module>(&mut self) -> Result<Dependency<T>, ModuleError> {
-        let tid = TypeId::of::<T>();
-        if !self.is_loaded::<T>() {
-            let module = AnyModule::new(T::new(self).map_err(ModuleError::InitError)?);
-            for event in module.listeners.keys() {
-                self.subscribers
-                    .entry(*event)
-                    .or_default()
-                    .push(TypeId::of::<T>());
+        self.load::<T>(ModuleId::of::<T>())
+    }
+
+    /// Returns the instance of the module `T` identified by `key` as a `Dependency<T>`, loading it if not found.
+    ///
+    /// Keyed instances are independent from each other and from the unkeyed instance returned by [`Engine::dependency`],
+    /// for example one camera per player. A module can get its own key from [`Engine::loading_key`] in `Module::new`.
+    pub fn dependency_with_key<T: Module>(
+        &mut self,
+        key: &str,
+    ) -> Result<Dependency<T>, ModuleError> {
+        self.load::<T>(ModuleId::keyed::<T>(key))
+    }
+
+    /// Key of the module currently being initialized, `None` if it is not a keyed instance, see [`Engine::dependency_with_key`].
+    pub fn loading_key(&self) -> Option<&'static str> {
+        self.loading.last()?.id.key
+    }
+
+    fn load<T: Module>(&mut self, id: ModuleId) -> Result<Dependency<T>, ModuleError> {
+        let dependency = self.instantiate::<T>(id)?;
+        // Only recorded once loaded, modules that failed to load are not dependencies
+        if let Some(loading) = self.loading.last_mut() {
+            if !loading.dependencies.contains(&id) {
+                loading.dependencies.push(id);
+            }
+        }
+        Ok(dependency)
+    }
+
+    fn instantiate<T: Module>(&mut self, id: ModuleId) -> Result<Dependency<T>, ModuleError> {
+        // The module is only inserted once `Module::new` returns
+        if let Some(start) = self.loading.iter().position(|loading| loading.id == id) {
+            let mut chain = self.loading[start..]
+                .iter()
+                .map(|loading| loading.id.name)
+                .collect::<Vec<_>>();
+            chain.push(id.name);
+            return Err(ModuleError::CircularDependency(chain));
+        }
+        if !self.modules.contains_key(&id) {
+            self.loading.push(Loading {
+                id,
+                dependencies: Vec::new(),
+                provides: Vec::new(),
+                started_at: Instant::now(),
+            });
+            let state = T::new(self);
+            let mut loading = self.loading.pop().unwrap();
+
+            // Forwarded as is through the modules of the path, instead of nesting init errors
+            let state = state.map_err(|source| match source.downcast::<ModuleError>() {
+                Ok(e)
+                    if matches!(
+                        *e,
+                        ModuleError::InitError { .. } | ModuleError::CircularDependency(_)
+                    ) =>
+                {
+                    *e
+                }
+                Ok(e) => self.init_error::<T>(e),
+                Err(e) => self.init_error::<T>(e),
+            })?;
+            let provides = std::mem::take(&mut loading.provides);
+            let module = AnyModule::new(state, loading);
+            let events = module.listeners.keys().copied().collect::<Vec<_>>();
+            if let Some(slot) = self.soft_slots.get(&id) {
+                *slot.borrow_mut() = Rc::downgrade(&module.state);
+            }
+            self.modules.insert(id, module);
+            self.load_order.push(id);
+
+            for event in events {
+                if let Err(e) = self.order_subscribers(event) {
+                    self.remove_module(id);
+                    return Err(e);
+                }
+            }
+            self.providers.extend(provides);
+
+            let dependency = self.instantiate::<T>(id)?;
+            dependency
+                .state
+                .try_borrow_mut(Some(dependency.state.name))
+                .unwrap_or_else(|e| panic!("Failed to call on_load: {e}"))
+                .downcast_mut::<T>()
+                .unwrap()
+                .on_load();
+            return Ok(dependency);
+        }
+        Ok(Dependency::new(
+            &self.modules.get(&id).unwrap().state,
+            self.loading.last().map(|loading| loading.id.name),
+        ))
+    }
+
+    /// Returns the module `T` as a `Dependency<T>` if it is already loaded or can be loaded,
+    /// `None` if it failed to load.
+    pub fn optional_dependency<T: Module>(&mut self) -> Option<Dependency<T>> {
+        self.dependency::<T>()
+            .inspect_err(|e| info!("Optional module {} was not loaded: {}", type_name::<T>(), e))
+            .ok()
+    }
+
+    /// Returns a handle to the module `T`, giving access to it whenever it is loaded, see [`SoftDependency`].
+    ///
+    /// The module is not loaded by this call.
+    pub fn soft_dependency<T: Module>(&mut self) -> SoftDependency<T> {
+        let id = ModuleId::of::<T>();
+        let state = self.modules.get(&id).map(|module| &module.state);
+        let slot = self
+            .soft_slots
+            .entry(id)
+            .or_insert_with(|| Rc::new(RefCell::new(state.map_or_else(Weak::new, Rc::downgrade))));
+        SoftDependency::new(slot, self.loading.last().map(|loading| loading.id.name))
+    }
+
+    /// Registers the module `M` as the provider of the interface `I`, usually a trait object like `dyn Renderer`,
+    /// so that other modules can request it with [`Engine::interface`] without naming `M`.
+    ///
+    /// Usually called from `M::new`, for example `ctx.provide::<Self, dyn Renderer>(|m| m, |m| m)`,
+    /// in which case the provider is the instance of `M` being initialized.
+    /// If another module already provides `I` it is replaced. The registration is dropped when `M` is unloaded.
+    ///
+    /// When called while `M` is loading, the registration only takes effect once `M` is loaded,
+    /// so that a module failing to load doesn't replace the current provider.
+    pub fn provide<M: Module, I: ?Sized + 'static>(
+        &mut self,
+        read: fn(&M) -> &I,
+        write: fn(&mut M) -> &mut I,
+    ) {
+        let interface = TypeId::of::<I>();
+        let casts = Box::new(Casts::new(read, write));
+        match self
+            .loading
+            .iter_mut()
+            .rev()
+            .find(|loading| loading.id.type_id == TypeId::of::<M>())
+        {
+            Some(loading) => {
+                let module = loading.id;
+                loading
+                    .provides
+                    .push((interface, Provider { module, casts }))
+            }
+            None => {
+                let module = ModuleId::of::<M>();
+                self.providers.insert(interface, Provider { module, casts });
+            }
+        }
+    }
+
+    /// Returns the module providing the interface `I` as a `Dependency<I>`, see [`Engine::provide`].
+    ///
+    /// Unlike [`Engine::dependency`] the provider is not loaded by this call,
+    /// [`ModuleError::NotFound`] is returned if no loaded module provides `I`.
+    pub fn interface<I: ?Sized + 'static>(&mut self) -> Result<Dependency<I>, ModuleError> {
+        let provider = self
+            .providers
+            .get(&TypeId::of::<I>())
+            .ok_or(ModuleError::NotFound)?;
+        let module = self
+            .modules
+            .get(&provider.module)
+            .ok_or(ModuleError::NotFound)?;
+        let casts = provider.casts.downcast_ref::<Casts<I>>().unwrap().clone();
+        let dependency = Dependency::with_casts(
+            &module.state,
+            self.loading.last().map(|loading| loading.id.name),
+            casts,
+        );
+        if let Some(loading) = self.loading.last_mut() {
+            if !loading.dependencies.contains(&provider.module) {
+                loading.dependencies.push(provider.module);
             }
-            self.modules.insert(tid, module);
-            return Ok(self.dependency()?);
         }
-        Ok(Dependency::new(self.modules.get(&tid).unwrap()))
+        Ok(dependency)
+    }
+
+    /// Returns the module providing the interface `I` as a `DependencyMut<I>`, see [`Engine::interface`].
+    pub fn interface_mut<I: ?Sized + 'static>(&mut self) -> Result<DependencyMut<I>, ModuleError> {
+        Ok(DependencyMut {
+            inner: self.interface()?,
+        })
+    }
+
+    fn init_error<T: Module>(&self, source: Box<dyn Error>) -> ModuleError {
+        ModuleError::InitError {
+            module: type_name::<T>(),
+            path: self
+                .loading
+                .iter()
+                .map(|loading| loading.id.name)
+                .chain([type_name::<T>()])
+                .collect(),
+            source,
+        }
+    }
+
+    /// Returns the module `T` as a `DependencyMut<T>`, loading it if not found.
+    ///
+    /// Same as [`Engine::dependency`] but explicitly declares that the state of `T` will be written to.
+    pub fn dependency_mut<T: Module>(&mut self) -> Result<DependencyMut<T>, ModuleError> {
+        Ok(DependencyMut {
+            inner: self.dependency()?,
+        })
+    }
+
+    /// Returns the instance of the module `T` identified by `key` as a `DependencyMut<T>`, loading it if not found.
+    ///
+    /// Same as [`Engine::dependency_with_key`] but explicitly declares that the state of `T` will be written to.
+    pub fn dependency_mut_with_key<T: Module>(
+        &mut self,
+        key: &str,
+    ) -> Result<DependencyMut<T>, ModuleError> {
+        Ok(DependencyMut {
+            inner: self.dependency_with_key(key)?,
+        })
     }
 
     /// Unloads the module `T` and returns its current state.
     ///
-    /// In case the module is not already loaded, an error is returned instead.
+    /// In case the module is not already loaded or is still used as a dependency, an error is returned instead
+    /// and the module stays loaded.
     pub fn unload_module<T: Module>(&mut self) -> Result<T, ModuleError> {
-        let tid = TypeId::of::<T>();
+        self.unload::<T>(ModuleId::of::<T>())
+    }
+
+    /// Unloads the instance of the module `T` identified by `key`, see [`Engine::unload_module`].
+    pub fn unload_module_with_key<T: Module>(&mut self, key: &str) -> Result<T, ModuleError> {
+        self.unload::<T>(ModuleId::keyed::<T>(key))
+    }
 
-        if self.is_loaded::<T>() {
-            let module = self.modules.remove(&tid).unwrap();
-            for event in module.listeners.keys() {
-                self.subscribers.remove(event);
+    fn unload<T: Module>(&mut self, id: ModuleId) -> Result<T, ModuleError> {
+        let module = self.modules.get(&id).ok_or(ModuleError::NotFound)?;
+        if Rc::strong_count(&module.state) > 1 {
+            return Err(ModuleError::InUse);
+        }
+
+        let module = self.remove_module(id);
+        let mut state = Rc::into_inner(module.state).unwrap().into_inner();
+        (module.on_unload)(&mut state);
+        Ok(*state.downcast::<T>().unwrap())
+    }
+
+    /// Removes the module from the engine and unsubscribes it from all of its events.
+    fn remove_module(&mut self, id: ModuleId) -> AnyModule {
+        let module = self.modules.remove(&id).unwrap();
+        self.load_order.retain(|other| *other != id);
+        for event in module.listeners.keys() {
+            if let Some(subscribers) = self.subscribers.get_mut(event) {
+                subscribers.retain(|other| *other != id);
+                if subscribers.is_empty() {
+                    self.subscribers.remove(event);
+                }
             }
-            let state = Rc::into_inner(module.state).ok_or(ModuleError::InUse)?;
-            Ok(*state.into_inner().downcast::<T>().unwrap())
-        } else {
-            Err(ModuleError::NotFound)
         }
+        // Registered for the module type, shared by its instances
+        if !self
+            .load_order
+            .iter()
+            .any(|other| other.type_id == id.type_id)
+        {
+            #[cfg(feature = "snapshot")]
+            self.snapshots.remove(&id.type_id);
+            self.debuggers.remove(&id.type_id);
+        }
+        self.providers.retain(|_, provider| provider.module != id);
+        module
+    }
+
+    /// Sorts the subscribers of `event` according to their [`ListenerOrder`](events::ListenerOrder),
+    /// falling back to the load order of the modules.
+    fn order_subscribers(&mut self, event: TypeId) -> Result<(), ModuleError> {
+        let mut pending = self
+            .load_order
+            .iter()
+            .filter_map(|id| {
+                let listener = self.modules.get(id)?.listeners.get(&event)?;
+                Some((*id, listener))
+            })
+            .collect::<Vec<_>>();
+
+        // Ordering constraints target module types, so they apply to every instance
+        let must_precede = |a: &(ModuleId, &RawListener<_>), b: &(ModuleId, &RawListener<_>)| {
+            a.1.order.before.contains(&b.0.type_id) || b.1.order.after.contains(&a.0.type_id)
+        };
+
+        let mut sorted = Vec::with_capacity(pending.len());
+        while !pending.is_empty() {
+            let next = pending
+                .iter()
+                .enumerate()
+                .filter(|(_, l)| !pending.iter().any(|other| must_precede(other, l)))
+                .min_by_key(|(i, l)| (Reverse(l.1.order.priority), *i))
+                .map(|(i, _)| i)
+                .ok_or_else(|| ModuleError::OrderingCycle {
+                    event: pending[0].1.event_name,
+                    modules: pending.iter().map(|(id, _)| id.name).collect(),
+                })?;
+            sorted.push(pending.remove(next).0);
+        }
+
+        self.subscribers.insert(event, sorted);
+        Ok(())
     }
 
     /// Check if a module is loadedd
     pub fn is_loaded<T: Module>(&self) -> bool {
-        self.modules.contains_key(&TypeId::of::<T>())
+        self.modules.contains_key(&ModuleId::of::<T>())
+    }
+
+    /// Check if the instance of the module `T` identified by `key` is loaded, see [`Engine::dependency_with_key`]
+    pub fn is_loaded_with_key<T: Module>(&self, key: &str) -> bool {
+        self.modules.contains_key(&ModuleId::keyed::<T>(key))
     }
 
     /// Dispatch the event `T` to all subscribed modules
-    /// and continue dispatching events until the [`EventQueue`] is empty.
+    /// and continue dispatching events until the [`EventQueue`] is empty,
+    /// then runs the commands queued with [`EventQueue::push_command`].
+    ///
+    /// Panics if the schedule exceeds its budget, see [`Self::try_run_with`].
     pub fn run_with<T: Event>(&mut self, event: T) {
-        let mut root_event_queue = EventQueue::new();
-        root_event_queue.push(event);
+        if let Err(e) = self.try_run_with(event) {
+            panic!("{e}")
+        }
+    }
+
+    /// Dispatch the event `T` to the module `target` only, if it listens to it, see [`EventQueue::push_to`].
+    ///
+    /// Panics if the schedule exceeds its budget, like [`Self::run_with`].
+    /// Targeted events are not recorded by the [`EventRecorder`](replay::EventRecorder).
+    pub fn run_with_target<T: Event>(&mut self, target: ModuleId, event: T) {
+        self.run_schedule(Box::new(Targeted::new(target, event)))
+    }
+
+    /// Same as [`Self::run_with`], but returns an error if the schedule exceeds its [`ScheduleBudget`],
+    /// in which case the events left in the schedule are dropped.
+    /// The commands queued by the listeners that already ran (see [`EventQueue::push_command`]) are still applied before returning.
+    pub fn try_run_with<T: Event>(&mut self, event: T) -> Result<(), ScheduleError> {
+        #[cfg(feature = "replay")]
+        let recorded = self.record_event(&event);
+        let result = self.try_run_schedule(Box::new(event));
+        #[cfg(feature = "replay")]
+        if recorded {
+            self.record_state_hash();
+        }
+        result
+    }
+
+    /// Advances the deferred events by one platform update and dispatches the ones that are due,
+    /// see [`EventQueue::push_after`], then dispatches the outputs of the completed tasks, see [`EventQueue::spawn`].
+    ///
+    /// Platforms call it once every iteration, right before dispatching the `PlatformUpdateEvent`.
+    ///
+    /// Panics if a schedule exceeds its budget, like [`Self::run_with`].
+    pub fn flush_deferred_events(&mut self) {
+        #[cfg(feature = "replay")]
+        self.record_flush();
+        if let Some(profiler) = &mut self.profiler {
+            profiler.end_frame();
+        }
+        for event in self.timers.advance() {
+            self.run_schedule(event);
+        }
+        for event in self.executor.poll() {
+            self.run_schedule(event);
+        }
+    }
+
+    /// Sets the order in which the events pushed by listeners are dispatched, see [`DispatchPolicy`].
+    pub fn set_dispatch_policy(&mut self, policy: DispatchPolicy) {
+        self.dispatch_policy = policy;
+    }
+
+    pub fn dispatch_policy(&self) -> &DispatchPolicy {
+        &self.dispatch_policy
+    }
+
+    /// Sets the limits on the events dispatched by a single schedule, see [`ScheduleBudget`].
+    pub fn set_schedule_budget(&mut self, budget: ScheduleBudget) {
+        self.schedule_budget = budget;
+    }
+
+    pub fn schedule_budget(&self) -> &ScheduleBudget {
+        &self.schedule_budget
+    }
+
+    fn run_schedule(&mut self, root_event: Box<dyn Event>) {
+        if let Err(e) = self.try_run_schedule(root_event) {
+            panic!("{e}")
+        }
+    }
+
+    fn try_run_schedule(&mut self, root_event: Box<dyn Event>) -> Result<(), ScheduleError> {
+        let mut schedule = VecDeque::from([Scheduled::root(root_event)]);
+        let started_at = Instant::now();
+        let mut dispatches = 0;
+        let mut commands = Vec::new();
+        let mut result = Ok(());
 
         #[cfg(feature = "debuglog")]
         debug!("NEW SCHEDULE:");
-        while let Some(event) = root_event_queue.take_last() {
+        while let Some(scheduled) = schedule.pop_front() {
             #[cfg(feature = "debuglog")]
-            let debug_name = events::DebugName::of(&*event);
+            let debug_name = events::format_type_name(scheduled.trace.event);
+
+            let budget = &self.schedule_budget;
+            // The root event is always dispatched, so that a schedule can't be deferred forever
+            if dispatches > 0
+                && budget
+                    .time_budget
+                    .is_some_and(|time_budget| started_at.elapsed() >= time_budget)
+            {
+                #[cfg(feature = "debuglog")]
+                debug!(
+                    " ~ Out of time, deferring {} event(s) to the next update.",
+                    schedule.len() + 1
+                );
+                self.timers
+                    .schedule(Delay::Updates(1), scheduled.into_event());
+                for scheduled in schedule.drain(..) {
+                    self.timers
+                        .schedule(Delay::Updates(1), scheduled.into_event());
+                }
+                break;
+            }
+            let Scheduled {
+                event,
+                target,
+                trace,
+            } = scheduled;
+            dispatches += 1;
+            if let Some(limit) = budget.max_dispatches.filter(|limit| dispatches > *limit) {
+                result = Err(ScheduleError::TooManyDispatches {
+                    limit,
+                    chain: trace.chain(),
+                });
+                break;
+            }
+            if let Some(limit) = budget.max_depth.filter(|limit| trace.depth > *limit) {
+                result = Err(ScheduleError::TooDeep {
+                    limit,
+                    chain: trace.chain(),
+                });
+                break;
+            }
 
             let mut event = event.as_any();
-            let Some(modules) = self.subscribers.get(&(&*event).type_id()) else {
+            let event_tid = (*event).type_id();
+            let Some(subscribers) = self.subscribers.get(&event_tid) else {
                 #[cfg(feature = "debuglog")]
                 debug!(" ~ No listener for {}", debug_name);
                 continue;
             };
+            let targeted;
+            let modules = match target {
+                Some(target) => {
+                    targeted = subscribers
+                        .iter()
+                        .filter(|id| **id == target)
+                        .copied()
+                        .collect::<Vec<_>>();
+                    &targeted
+                }
+                None => subscribers,
+            };
 
             #[cfg(feature = "debuglog")]
             debug!(
@@ -188,82 +892,198 @@ impl Engine {
             );
 
             let mut event_queue = EventQueue::new();
+            let dispatch_started_at = Instant::now();
+
+            #[cfg(feature = "parallel")]
+            let batches = parallel::batches(&self.modules, event_tid, modules);
+            #[cfg(not(feature = "parallel"))]
+            let batches = modules.chunks(1);
+
+            for batch in batches {
+                match batch {
+                    #[cfg(feature = "parallel")]
+                    [_, _, ..] => {
+                        let timings = parallel::dispatch_batch(
+                            &self.modules,
+                            event_tid,
+                            batch,
+                            &*event,
+                            &mut event_queue,
+                        );
+                        if let Some(profiler) = &mut self.profiler {
+                            for (id, (start, duration, thread)) in batch.iter().zip(timings) {
+                                profiler.record_listener(*id, trace.event, start, duration, thread);
+                            }
+                        }
+                    }
+                    _ => {
+                        for id in batch {
+                            if let Some(module) = self.modules.get_mut(id) {
+                                event_queue.current = Some(*id);
+                                let listener_started_at = Instant::now();
+                                module.handle_event(event.as_mut(), &mut event_queue);
+                                if let Some(profiler) = &mut self.profiler {
+                                    profiler.record_listener(
+                                        *id,
+                                        trace.event,
+                                        listener_started_at,
+                                        listener_started_at.elapsed(),
+                                        0,
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if event_queue.is_consumed() {
+                    #[cfg(feature = "debuglog")]
+                    debug!(
+                        " ~ {} consumed by {}, skipping {} module(s).",
+                        debug_name,
+                        batch
+                            .iter()
+                            .map(|id| id.format())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        modules.len()
+                            - 1
+                            - modules
+                                .iter()
+                                .position(|t| Some(t) == batch.last())
+                                .unwrap()
+                    );
+                    break;
+                }
+            }
 
-            for tid in modules {
-                self.modules
-                    .get_mut(tid)
-                    .map(|m| m.handle_event(event.as_mut(), &mut event_queue));
+            if let Some(profiler) = &mut self.profiler {
+                profiler.record_event(
+                    trace.event,
+                    dispatch_started_at,
+                    dispatch_started_at.elapsed(),
+                );
             }
 
-            root_event_queue.extend(event_queue);
+            for (delay, event) in event_queue.deferred.drain(..) {
+                self.timers.schedule(delay, event);
+            }
+            commands.append(&mut event_queue.commands);
+            for task in event_queue.tasks.drain(..) {
+                self.executor.spawn(task);
+            }
+            let children = event_queue
+                .take_events()
+                .into_iter()
+                .map(|child| Scheduled::child(child, &trace));
+            match self.dispatch_policy.order_of(event_tid) {
+                DispatchOrder::DepthFirst => {
+                    children.rev().for_each(|child| schedule.push_front(child))
+                }
+                DispatchOrder::BreadthFirst => schedule.extend(children),
+            }
         }
+
+        // Commands of the listeners that ran are applied even if the budget was exceeded,
+        // as their state may rely on them (modules to load or unload...)
+        for command in commands {
+            command(self);
+        }
+        result
     }
 }
 
-type ModuleListener<T> = HashMap<TypeId, Box<dyn Fn(&mut T, &mut dyn Any, &mut EventQueue)>>;
-type AnyListener = Box<dyn Fn(RefMut<Box<dyn Any>>, &mut dyn Any, &mut EventQueue)>;
+impl Drop for Engine {
+    fn drop(&mut self) {
+        while let Some(id) = self.load_order.last().copied() {
+            let module = self.remove_module(id);
+            if let Ok(mut state) = module.state.try_borrow_mut(Some(module.state.name)) {
+                (module.on_unload)(&mut state);
+            };
+        }
+    }
+}
+
+/// A module being initialized
+struct Loading {
+    id: ModuleId,
+    /// Modules requested so far
+    dependencies: Vec<ModuleId>,
+    /// Interfaces provided so far, registered once the module is loaded
+    provides: Vec<(TypeId, Provider)>,
+    started_at: Instant,
+}
+
+type ModuleListener<T> = HashMap<TypeId, RawListener<T>>;
 
-type ModuleState = Rc<RefCell<Box<dyn Any>>>;
+/// A module providing an interface, see [`Engine::provide`]
+struct Provider {
+    module: ModuleId,
+    /// `Casts<I>` of the interface `I`
+    casts: Box<dyn Any>,
+}
 
 struct AnyModule {
     state: ModuleState,
-    listeners: HashMap<TypeId, Box<dyn Fn(RefMut<Box<dyn Any>>, &mut dyn Any, &mut EventQueue)>>,
+    listeners: ModuleListener<Box<dyn Any>>,
+    /// Modules requested by this module when it was initialized
+    dependencies: Vec<ModuleId>,
+    /// Type names of the events the module declared to push, see [`Listeners::emits`]
+    emits: Vec<&'static str>,
+    /// Time spent in `Module::new`, including the loading of its dependencies
+    init_time: Duration,
+    on_unload: fn(&mut Box<dyn Any>),
 }
 
 impl AnyModule {
-    fn new<T: Module>(state: T) -> AnyModule {
+    fn new<T: Module>(state: T, loading: Loading) -> AnyModule {
+        let mut listeners = Listeners::new();
+        listeners.inner = T::ListeningTo::raw_listeners();
+        T::listeners(&mut listeners);
         Self {
-            state: Rc::new(RefCell::new(Box::new(state))),
-            listeners: T::ListeningTo::raw_listeners()
+            state: ModuleCell::new(state),
+            dependencies: loading.dependencies,
+            emits: listeners.emits,
+            init_time: loading.started_at.elapsed(),
+            listeners: listeners
+                .inner
                 .into_iter()
-                .map(|(tid, callback)| {
+                .map(|(tid, listener)| {
+                    let callback = listener.callback;
                     (
                         tid,
-                        Box::new(
-                            move |mut any_self: RefMut<Box<dyn Any>>,
-                                  any_event: &mut dyn Any,
-                                  event_queue: &mut EventQueue| {
-                                callback(
-                                    any_self.as_mut().downcast_mut().unwrap(),
-                                    any_event,
-                                    event_queue,
-                                )
-                            },
-                        ) as AnyListener,
+                        RawListener {
+                            event_name: listener.event_name,
+                            order: listener.order,
+                            #[cfg(feature = "parallel")]
+                            parallel: listener.parallel,
+                            callback: Box::new(
+                                move |any_self: &mut Box<dyn Any>,
+                                      any_event: &mut dyn Any,
+                                      event_queue: &mut EventQueue| {
+                                    callback(
+                                        any_self.as_mut().downcast_mut().unwrap(),
+                                        any_event,
+                                        event_queue,
+                                    )
+                                },
+                            ),
+                        },
                     )
                 })
                 .collect(),
+            on_unload: |state| state.downcast_mut::<T>().unwrap().on_unload(),
         }
     }
 
     // Should only be called if the module have subscribed to the event!
     fn handle_event(&mut self, event: &mut dyn Any, event_queue: &mut EventQueue) {
-        if let Some(callback) = self.listeners.get(&(&*event).type_id()) {
-            callback((*self.state).borrow_mut(), event, event_queue)
+        if let Some(listener) = self.listeners.get(&(*event).type_id()) {
+            let mut state = self
+                .state
+                .try_borrow_mut(Some(self.state.name))
+                .unwrap_or_else(|e| panic!("Failed to dispatch {}: {e}", listener.event_name));
+            (listener.callback)(&mut state, event, event_queue)
         };
     }
 }
-
-/// An immutable handle to a `Module`.
-///
-/// You can read it's state with the `read_state(&self)` method.
-pub struct Dependency<T: Module> {
-    _marker: PhantomData<T>,
-    state: ModuleState,
-}
-
-impl<T: Module> Dependency<T> {
-    fn new(module: &AnyModule) -> Self {
-        Self {
-            _marker: PhantomData,
-            state: module.state.clone(),
-        }
-    }
-
-    /// Read the module state immutably
-    pub fn read_state(&self) -> Ref<'_, T> {
-        Ref::map((*self.state).borrow(), |state| {
-            state.downcast_ref::<T>().unwrap()
-        })
-    }
-}
@@ -0,0 +1,203 @@
+use std::{
+    any::{Any, TypeId},
+    time::{Duration, Instant},
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    events::{Event, EventQueue, ListenerOrder, RawCallback, RawListener},
+    ModuleId, Modules,
+};
+
+/// Allows for module to listen to Event `T` from a thread pool, concurrently with the other parallel listeners of `T`.
+///
+/// Parallel listeners of the same event are dispatched concurrently unless their modules depend on each other
+/// (see [`Engine::dependency`](crate::Engine::dependency)) or their [`ListenerOrder`] constrain them,
+/// in which case they are dispatched one after the other, like regular listeners.
+///
+/// **WARNING: For this to work you need to register the event with
+/// [`Listeners::listen_parallel`](crate::events::Listeners::listen_parallel) in [`Module::listeners`](crate::Module::listeners)**
+pub trait ParallelListener<T: Event + Sync>: Send + 'static {
+    fn on_event(&mut self, event: &T, queue: &mut ParallelQueue);
+
+    /// Constraints on when this listener is dispatched relative to the other listeners of `T`, see [`Listener::order`](crate::events::Listener::order).
+    fn order() -> ListenerOrder
+    where
+        Self: Sized,
+    {
+        ListenerOrder::default()
+    }
+}
+
+/// Queue of the events pushed by a [`ParallelListener`], dispatched once every listener of the event is done.
+pub struct ParallelQueue {
+    inner: Vec<Box<dyn Event + Send>>,
+    consumed: bool,
+}
+
+impl ParallelQueue {
+    fn new() -> Self {
+        Self {
+            inner: Vec::new(),
+            consumed: false,
+        }
+    }
+
+    /// Pushes a new event `T` into the event queue to be dispatched.
+    pub fn push<T: Event + Send>(&mut self, event: T) {
+        self.inner.push(Box::new(event))
+    }
+
+    /// Marks the event currently being handled as consumed, see [`EventQueue::consume`].
+    ///
+    /// The listeners dispatched concurrently with this one still receive it.
+    pub fn consume(&mut self) {
+        self.consumed = true;
+    }
+
+    fn append_to(self, queue: &mut EventQueue) {
+        queue.extend_boxed(self.inner.into_iter().map(|event| event as Box<dyn Event>));
+        if self.consumed {
+            queue.consume();
+        }
+    }
+}
+
+/// Type-erased [`ParallelListener`] callback, only manipulating `Send`/`Sync` views of the module and the event.
+pub(crate) struct RawParallelCallback {
+    as_send: fn(&mut dyn Any) -> &mut (dyn Any + Send),
+    as_sync: fn(&dyn Any) -> &(dyn Any + Sync),
+    callback: fn(&mut (dyn Any + Send), &(dyn Any + Sync), &mut ParallelQueue),
+}
+
+pub(crate) fn raw_listener<T: ParallelListener<E>, E: Event + Sync>() -> RawListener<T> {
+    let callback: RawCallback<T> = Box::new(|_self, any_event, event_queue| {
+        let mut queue = ParallelQueue::new();
+        ParallelListener::<E>::on_event(_self, any_event.downcast_ref().unwrap(), &mut queue);
+        queue.append_to(event_queue);
+    });
+    RawListener {
+        event_name: std::any::type_name::<E>(),
+        order: <T as ParallelListener<E>>::order(),
+        callback,
+        parallel: Some(RawParallelCallback {
+            as_send: |state| state.downcast_mut::<T>().unwrap(),
+            as_sync: |event| event.downcast_ref::<E>().unwrap(),
+            callback: |state, event, queue| {
+                ParallelListener::<E>::on_event(
+                    state.downcast_mut::<T>().unwrap(),
+                    (event as &dyn Any).downcast_ref().unwrap(),
+                    queue,
+                )
+            },
+        }),
+    }
+}
+
+/// Splits the ordered listeners of an event into consecutive batches that can be dispatched concurrently.
+pub(crate) fn batches<'a>(
+    modules: &Modules,
+    event: TypeId,
+    subscribers: &'a [ModuleId],
+) -> Vec<&'a [ModuleId]> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    for (i, id) in subscribers.iter().enumerate() {
+        let joins_batch = modules[id].listeners[&event].parallel.is_some()
+            && subscribers[start..i].iter().all(|other| {
+                modules[other].listeners[&event].parallel.is_some()
+                    && !conflicts(modules, event, *id, *other)
+            });
+        if !joins_batch && i > start {
+            batches.push(&subscribers[start..i]);
+            start = i;
+        }
+    }
+    if start < subscribers.len() {
+        batches.push(&subscribers[start..]);
+    }
+    batches
+}
+
+/// Two listeners conflict if one of the modules depends on the other, or if their ordering is constrained.
+fn conflicts(modules: &Modules, event: TypeId, a: ModuleId, b: ModuleId) -> bool {
+    let constrains = |a: ModuleId, b: ModuleId| {
+        let order = &modules[&a].listeners[&event].order;
+        order.before.contains(&b.type_id) || order.after.contains(&b.type_id)
+    };
+    depends_on(modules, a, b) || depends_on(modules, b, a) || constrains(a, b) || constrains(b, a)
+}
+
+/// Whether `module` transitively depends on `target`
+fn depends_on(modules: &Modules, module: ModuleId, target: ModuleId) -> bool {
+    let mut stack = vec![module];
+    let mut visited = Vec::new();
+    while let Some(id) = stack.pop() {
+        if id == target {
+            return true;
+        }
+        if !visited.contains(&id) {
+            visited.push(id);
+            stack.extend(modules.get(&id).into_iter().flat_map(|m| &m.dependencies));
+        }
+    }
+    false
+}
+
+/// Dispatches an event to a batch of parallel listeners on the rayon thread pool.
+///
+/// Returns when each listener started, its duration and the index of its thread in the pool plus one.
+pub(crate) fn dispatch_batch(
+    modules: &Modules,
+    event_tid: TypeId,
+    batch: &[ModuleId],
+    event: &dyn Any,
+    event_queue: &mut EventQueue,
+) -> Vec<(Instant, Duration, usize)> {
+    let mut states = batch
+        .iter()
+        .map(|id| {
+            let module = &modules[id];
+            let listener = &module.listeners[&event_tid];
+            let state = module
+                .state
+                .try_borrow_mut(Some(module.state.name))
+                .unwrap_or_else(|e| panic!("Failed to dispatch {}: {e}", listener.event_name));
+            (state, listener.parallel.as_ref().unwrap())
+        })
+        .collect::<Vec<_>>();
+    let mut queues = batch
+        .iter()
+        .map(|_| ParallelQueue::new())
+        .collect::<Vec<_>>();
+    let mut timings = vec![None; batch.len()];
+
+    let jobs = states
+        .iter_mut()
+        .zip(&mut queues)
+        .zip(&mut timings)
+        .map(|(((state, raw), queue), timing)| {
+            (
+                raw.callback,
+                (raw.as_send)(&mut ***state),
+                (raw.as_sync)(event),
+                queue,
+                timing,
+            )
+        })
+        .collect::<Vec<_>>();
+    jobs.into_par_iter()
+        .for_each(|(callback, state, event, queue, timing)| {
+            let start = Instant::now();
+            callback(state, event, queue);
+            let thread = rayon::current_thread_index().map_or(0, |index| index + 1);
+            *timing = Some((start, start.elapsed(), thread));
+        });
+
+    drop(states);
+    for queue in queues {
+        queue.append_to(event_queue);
+    }
+    timings.into_iter().map(Option::unwrap).collect()
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{events::format_type_name, introspection::json_string, Engine, ModuleId};
+
+/// Records the time spent by every listener in [`Engine::run_with`], see [`Engine::start_profiling`].
+///
+/// Listener timings are summarized per frame, a frame being the time between two calls to
+/// [`Engine::flush_deferred_events`] (i.e. a platform update), and can be exported as a Chrome trace
+/// once enabled with [`Profiler::trace`].
+pub struct Profiler {
+    slowest: usize,
+    history: usize,
+    trace: bool,
+    started_at: Instant,
+    frame: FrameProfile,
+    frame_started_at: Instant,
+    frames: VecDeque<FrameProfile>,
+    spans: Vec<Span>,
+}
+
+/// Time spent by a module handling an event
+#[derive(Clone, Debug)]
+pub struct ListenerTiming {
+    pub module: ModuleId,
+    /// Type name of the event
+    pub event: &'static str,
+    pub duration: Duration,
+}
+
+impl Display for ListenerTiming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} on {}: {:?}",
+            self.module.format(),
+            format_type_name(self.event),
+            self.duration
+        )
+    }
+}
+
+/// Summary of the listeners dispatched during a frame, see [`Profiler::frames`].
+#[derive(Clone, Debug, Default)]
+pub struct FrameProfile {
+    /// Number of frames profiled before this one
+    pub index: u64,
+    pub duration: Duration,
+    /// Time spent in listeners, parallel listeners being summed up
+    pub listeners_time: Duration,
+    /// Number of listeners dispatched
+    pub listeners: usize,
+    /// Slowest listeners of the frame, slowest first, see [`Profiler::slowest_listeners`]
+    pub slowest: Vec<ListenerTiming>,
+}
+
+impl Display for FrameProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Frame {} [{:?}, {} listener(s) in {:?}]",
+            self.index, self.duration, self.listeners, self.listeners_time
+        )?;
+        for timing in &self.slowest {
+            writeln!(f, "    {}", timing)?;
+        }
+        Ok(())
+    }
+}
+
+enum Span {
+    Event {
+        event: &'static str,
+        start: Duration,
+        duration: Duration,
+    },
+    Listener {
+        timing: ListenerTiming,
+        start: Duration,
+        thread: usize,
+    },
+    Frame {
+        index: u64,
+        at: Duration,
+    },
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            slowest: 10,
+            history: 120,
+            trace: false,
+            started_at: Instant::now(),
+            frame: FrameProfile::default(),
+            frame_started_at: Instant::now(),
+            frames: VecDeque::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Number of listeners kept in [`FrameProfile::slowest`], 10 by default.
+    pub fn slowest_listeners(mut self, count: usize) -> Self {
+        self.slowest = count;
+        self
+    }
+
+    /// Number of frames kept in [`Profiler::frames`], the oldest ones being dropped first, 120 by default.
+    pub fn history(mut self, frames: usize) -> Self {
+        self.history = frames;
+        self
+    }
+
+    /// Keeps every listener timing for [`Profiler::chrome_trace`], disabled by default.
+    ///
+    /// The trace grows with the run, only the per-frame summaries are kept when disabled.
+    pub fn trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Summaries of the last completed frames, oldest first
+    pub fn frames(&self) -> impl Iterator<Item = &FrameProfile> {
+        self.frames.iter()
+    }
+
+    pub fn last_frame(&self) -> Option<&FrameProfile> {
+        self.frames.back()
+    }
+
+    /// Longest of the frames kept in [`Profiler::frames`]
+    pub fn slowest_frame(&self) -> Option<&FrameProfile> {
+        self.frames.iter().max_by_key(|frame| frame.duration)
+    }
+
+    /// Recorded spans in the Chrome trace event format, to open with `chrome://tracing` or Perfetto.
+    /// Empty unless the trace was enabled with [`Profiler::trace`].
+    ///
+    /// The start of every frame is marked with a global instant event.
+    ///
+    /// Events and listeners dispatched on the main thread are on thread 0,
+    /// [`ParallelListener`](crate::parallel::ParallelListener)s are on the thread of the pool that ran them.
+    pub fn chrome_trace(&self) -> String {
+        let micros = |duration: &Duration| format!("{:.3}", duration.as_secs_f64() * 1e6);
+        let mut events = vec![
+            r#"{"name":"thread_name","ph":"M","pid":1,"tid":0,"args":{"name":"main"}}"#.to_owned(),
+        ];
+        for span in &self.spans {
+            events.push(match span {
+                Span::Event {
+                    event,
+                    start,
+                    duration,
+                } => format!(
+                    r#"{{"name":{},"cat":"event","ph":"X","ts":{},"dur":{},"pid":1,"tid":0}}"#,
+                    json_string(event),
+                    micros(start),
+                    micros(duration)
+                ),
+                Span::Listener {
+                    timing,
+                    start,
+                    thread,
+                } => format!(
+                    r#"{{"name":{},"cat":"listener","ph":"X","ts":{},"dur":{},"pid":1,"tid":{},"args":{{"event":{}}}}}"#,
+                    json_string(&timing.module.to_string()),
+                    micros(start),
+                    micros(&timing.duration),
+                    thread,
+                    json_string(timing.event)
+                ),
+                Span::Frame { index, at } => format!(
+                    r#"{{"name":"Frame {}","cat":"frame","ph":"i","s":"g","ts":{},"pid":1,"tid":0}}"#,
+                    index,
+                    micros(at)
+                ),
+            });
+        }
+        format!(
+            "{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[\n{}\n]}}\n",
+            events.join(",\n")
+        )
+    }
+
+    pub fn save_chrome_trace(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.chrome_trace())
+    }
+
+    /// `thread` is the index of the thread in the pool plus one, 0 for the main thread.
+    pub(crate) fn record_listener(
+        &mut self,
+        module: ModuleId,
+        event: &'static str,
+        start: Instant,
+        duration: Duration,
+        thread: usize,
+    ) {
+        let timing = ListenerTiming {
+            module,
+            event,
+            duration,
+        };
+        self.frame.listeners += 1;
+        self.frame.listeners_time += duration;
+
+        let slowest = &mut self.frame.slowest;
+        let index = slowest.partition_point(|other| other.duration >= duration);
+        if index < self.slowest {
+            slowest.insert(index, timing.clone());
+            slowest.truncate(self.slowest);
+        }
+
+        if self.trace {
+            self.spans.push(Span::Listener {
+                timing,
+                start: start.duration_since(self.started_at),
+                thread,
+            });
+        }
+    }
+
+    pub(crate) fn record_event(&mut self, event: &'static str, start: Instant, duration: Duration) {
+        if self.trace {
+            self.spans.push(Span::Event {
+                event,
+                start: start.duration_since(self.started_at),
+                duration,
+            });
+        }
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        let now = Instant::now();
+        let index = self.frame.index + 1;
+        let mut frame = std::mem::replace(
+            &mut self.frame,
+            FrameProfile {
+                index,
+                ..Default::default()
+            },
+        );
+        frame.duration = now.duration_since(self.frame_started_at);
+        self.frame_started_at = now;
+
+        if self.history > 0 {
+            if self.frames.len() == self.history {
+                self.frames.pop_front();
+            }
+            self.frames.push_back(frame);
+        }
+        if self.trace {
+            self.spans.push(Span::Frame {
+                index,
+                at: now.duration_since(self.started_at),
+            });
+        }
+    }
+}
+
+impl Engine {
+    /// Starts recording the time spent by every listener, see [`Profiler`].
+    pub fn start_profiling(&mut self, mut profiler: Profiler) {
+        profiler.started_at = Instant::now();
+        profiler.frame_started_at = profiler.started_at;
+        self.profiler = Some(profiler);
+    }
+
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    /// Stops profiling and returns the profiler, if profiling was started.
+    pub fn stop_profiling(&mut self) -> Option<Profiler> {
+        self.profiler.take()
+    }
+}
@@ -0,0 +1,374 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{events::Event, Engine};
+
+/// An event that can be recorded by an [`EventRecorder`] and replayed by a [`Replayer`] when passed to [`Engine::run_with`].
+///
+/// **WARNING: For this to work the event also needs to be registered with [`EventRecorder::record`] and [`Replayer::event`]**
+pub trait RecordableEvent: Event + Serialize + DeserializeOwned {
+    /// Name of the event in the recordings, unique among the recordable events.
+    ///
+    /// Unlike the type name, it must not change between builds, for recordings to be replayed by newer versions of the game.
+    const NAME: &'static str;
+}
+
+/// Records the root events passed to [`Engine::run_with`], see [`Engine::start_recording`].
+///
+/// Events that are not registered are not recorded, so every event a platform feeds to the engine should be.
+/// Events deferred with a delay in time (see [`EventQueue::push_after`](crate::events::EventQueue::push_after))
+/// and the outputs of tasks (see [`EventQueue::spawn`](crate::events::EventQueue::spawn))
+/// depend on the wall-clock and can't be replayed deterministically.
+pub struct EventRecorder {
+    encoders: HashMap<TypeId, Encoder>,
+    /// First [`RecordableEvent::NAME`] registered by two different events
+    duplicate: Option<&'static str>,
+    hash_state: bool,
+    started_at: Instant,
+    recording: Recording,
+}
+
+type Encoder = (
+    &'static str,
+    fn(&dyn Any) -> Result<Value, serde_json::Error>,
+);
+
+impl Default for EventRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventRecorder {
+    pub fn new() -> Self {
+        Self {
+            encoders: HashMap::new(),
+            duplicate: None,
+            hash_state: false,
+            started_at: Instant::now(),
+            recording: Recording {
+                format: Recording::FORMAT,
+                frames: Vec::new(),
+            },
+        }
+    }
+
+    /// Records the events `E` passed to [`Engine::run_with`].
+    pub fn record<E: RecordableEvent>(mut self) -> Self {
+        let tid = TypeId::of::<E>();
+        if self
+            .encoders
+            .iter()
+            .any(|(other, (name, _))| *name == E::NAME && *other != tid)
+        {
+            self.duplicate.get_or_insert(E::NAME);
+        }
+        self.encoders.insert(
+            tid,
+            (E::NAME, |event| {
+                serde_json::to_value(event.downcast_ref::<E>().unwrap())
+            }),
+        );
+        self
+    }
+
+    /// Records a hash of the [`Snapshot`](crate::snapshot::Snapshot) of the engine after every recorded event,
+    /// checked by the [`Replayer`] to detect nondeterminism.
+    pub fn hash_state(mut self, hash_state: bool) -> Self {
+        self.hash_state = hash_state;
+        self
+    }
+}
+
+/// Root events recorded by an [`EventRecorder`], in dispatch order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    format: u32,
+    frames: Vec<Frame>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum Frame {
+    /// A call to [`Engine::flush_deferred_events`]
+    Flush,
+    Event {
+        /// Time since the start of the recording
+        at: Duration,
+        name: String,
+        event: Value,
+        state_hash: Option<u64>,
+    },
+}
+
+impl Recording {
+    /// Version of the recording format
+    pub const FORMAT: u32 = 1;
+
+    /// Number of recorded events
+    pub fn len(&self) -> usize {
+        self.events().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Names of the recorded events, in dispatch order
+    pub fn events(&self) -> impl Iterator<Item = &str> {
+        self.frames.iter().filter_map(|frame| match frame {
+            Frame::Event { name, .. } => Some(name.as_str()),
+            Frame::Flush => None,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, serde_json::to_vec(self)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let bytes = std::fs::read(path).map_err(ReplayError::Io)?;
+        let recording: Self = serde_json::from_slice(&bytes).map_err(ReplayError::Corrupted)?;
+        if recording.format != Self::FORMAT {
+            return Err(ReplayError::UnsupportedFormat(recording.format));
+        }
+        Ok(recording)
+    }
+}
+
+/// Feeds the events of a [`Recording`] back to an engine, usually a fresh one with the same entrypoint.
+pub struct Replayer {
+    decoders: HashMap<&'static str, (TypeId, Decoder)>,
+    /// First [`RecordableEvent::NAME`] registered by two different events
+    duplicate: Option<&'static str>,
+    paced: bool,
+}
+
+type Decoder = fn(&mut Engine, Value) -> Result<(), serde_json::Error>;
+
+impl Default for Replayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Replayer {
+    pub fn new() -> Self {
+        Self {
+            decoders: HashMap::new(),
+            duplicate: None,
+            paced: false,
+        }
+    }
+
+    /// Replays the recorded events `E`.
+    pub fn event<E: RecordableEvent>(mut self) -> Self {
+        let decoder: Decoder = |engine, event| {
+            engine.run_with(serde_json::from_value::<E>(event)?);
+            Ok(())
+        };
+        let tid = TypeId::of::<E>();
+        if let Some((other, _)) = self.decoders.insert(E::NAME, (tid, decoder)) {
+            if other != tid {
+                self.duplicate.get_or_insert(E::NAME);
+            }
+        }
+        self
+    }
+
+    /// Waits for the recorded timestamps before dispatching events instead of replaying them as fast as possible.
+    pub fn paced(mut self, paced: bool) -> Self {
+        self.paced = paced;
+        self
+    }
+
+    /// Dispatches every recorded event to `engine`, checking the state hashes if they were recorded.
+    pub fn run(&self, engine: &mut Engine, recording: &Recording) -> Result<(), ReplayError> {
+        if let Some(name) = self.duplicate {
+            return Err(ReplayError::DuplicateName(name));
+        }
+        let started_at = Instant::now();
+        for (index, frame) in recording.frames.iter().enumerate() {
+            let Frame::Event {
+                at,
+                name,
+                event,
+                state_hash,
+            } = frame
+            else {
+                engine.flush_deferred_events();
+                continue;
+            };
+
+            let (_, decoder) = self
+                .decoders
+                .get(name.as_str())
+                .ok_or_else(|| ReplayError::UnknownEvent(name.clone()))?;
+            if self.paced {
+                std::thread::sleep(at.saturating_sub(started_at.elapsed()));
+            }
+            decoder(engine, event.clone()).map_err(|source| ReplayError::Serde {
+                event: name.clone(),
+                source,
+            })?;
+
+            if let Some(expected) = *state_hash {
+                let found = engine.state_hash();
+                if found != Some(expected) {
+                    return Err(ReplayError::Diverged {
+                        frame: index,
+                        event: name.clone(),
+                        expected,
+                        found,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+/// Error relative to replays
+pub enum ReplayError {
+    /// Error occured while reading the recording file
+    Io(std::io::Error),
+    /// Error occured because the recording could not be parsed
+    Corrupted(serde_json::Error),
+    /// Error occured because the recording was made with another format version, see [`Recording::FORMAT`]
+    UnsupportedFormat(u32),
+    /// Error occured because two registered events have the same [`RecordableEvent::NAME`]
+    DuplicateName(&'static str),
+    /// Error occured because a recorded event was not registered with [`Replayer::event`]
+    UnknownEvent(String),
+    /// Error occured while deserializing a recorded event
+    Serde {
+        event: String,
+        source: serde_json::Error,
+    },
+    /// Error occured because the state of the engine differs from the recorded one
+    Diverged {
+        frame: usize,
+        event: String,
+        expected: u64,
+        found: Option<u64>,
+    },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(_) => write!(f, "Failed to read the recording"),
+            Self::Corrupted(_) => write!(f, "The recording could not be parsed"),
+            Self::UnsupportedFormat(format) => write!(
+                f,
+                "The recording format {} is not supported, expected {}",
+                format,
+                Recording::FORMAT
+            ),
+            Self::DuplicateName(name) => write!(f, "Several recordable events are named {}", name),
+            Self::UnknownEvent(event) => write!(f, "The recorded event {} is not registered", event),
+            Self::Serde { event, .. } => write!(f, "Failed to deserialize the recorded {}", event),
+            Self::Diverged {
+                frame,
+                event,
+                expected,
+                found,
+            } => write!(
+                f,
+                "The engine state diverged from the recording after {} (frame {}): expected hash {:016x}, found {}",
+                event,
+                frame,
+                expected,
+                found.map_or("none".to_owned(), |found| format!("{:016x}", found))
+            ),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Corrupted(e) | Self::Serde { source: e, .. } => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Engine {
+    /// Starts recording the root events passed to [`Engine::run_with`], replacing the current recording if any.
+    ///
+    /// Fails if two of the registered events have the same [`RecordableEvent::NAME`].
+    pub fn start_recording(&mut self, mut recorder: EventRecorder) -> Result<(), ReplayError> {
+        if let Some(name) = recorder.duplicate {
+            return Err(ReplayError::DuplicateName(name));
+        }
+        recorder.started_at = Instant::now();
+        self.recorder = Some(recorder);
+        Ok(())
+    }
+
+    /// Stops recording and returns the recorded events, if a recording was started.
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recorder.take().map(|recorder| recorder.recording)
+    }
+
+    /// Hash of the [`Snapshot`](crate::snapshot::Snapshot) of the engine, `None` if a module state is borrowed.
+    ///
+    /// The hash is stable across runs and platforms (FNV-1a).
+    pub fn state_hash(&self) -> Option<u64> {
+        let bytes = self.snapshot().ok()?.to_bytes();
+        Some(bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        }))
+    }
+
+    /// Records `event` if it is registered, returns whether it was.
+    pub(crate) fn record_event(&mut self, event: &dyn Any) -> bool {
+        let Some(recorder) = &mut self.recorder else {
+            return false;
+        };
+        let Some((name, encode)) = recorder.encoders.get(&event.type_id()) else {
+            return false;
+        };
+        let event = encode(event).unwrap_or_else(|e| panic!("Failed to record {}: {e}", name));
+        let at = recorder.started_at.elapsed();
+        recorder.recording.frames.push(Frame::Event {
+            at,
+            name: name.to_string(),
+            event,
+            state_hash: None,
+        });
+        true
+    }
+
+    /// Records the state hash after a recorded event has been dispatched.
+    pub(crate) fn record_state_hash(&mut self) {
+        if !self.recorder.as_ref().is_some_and(|r| r.hash_state) {
+            return;
+        }
+        let hash = self.state_hash();
+        if let Some(Frame::Event { state_hash, .. }) = self
+            .recorder
+            .as_mut()
+            .and_then(|recorder| recorder.recording.frames.last_mut())
+        {
+            *state_hash = hash;
+        }
+    }
+
+    pub(crate) fn record_flush(&mut self) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.recording.frames.push(Frame::Flush);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{BorrowError, Engine, Module, ModuleId};
+
+/// A module whose state can be captured and restored by [`Engine::snapshot`] and [`Engine::restore`].
+///
+/// **WARNING: For this to work the module needs to be registered with [`Engine::enable_snapshot`],
+/// for example from its `Module::new`**
+pub trait SnapshotModule: Module {
+    /// Serializable part of the state, dependencies and resources should be left out
+    type Snapshot: Serialize + DeserializeOwned;
+
+    /// Name of the module in the snapshots, unique among the snapshotted modules.
+    ///
+    /// Unlike the type name, it must not change between builds, for snapshots to be restored by newer versions of the game.
+    const NAME: &'static str;
+
+    /// Version of [`Self::Snapshot`], to be increased whenever its format changes
+    const VERSION: u32 = 0;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    fn restore(&mut self, snapshot: Self::Snapshot);
+}
+
+/// The state of every snapshotted module of an [`Engine`], keyed by [`SnapshotModule::NAME`] (followed by `[key]` for keyed instances).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    format: u32,
+    modules: BTreeMap<String, ModuleSnapshot>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ModuleSnapshot {
+    version: u32,
+    state: Value,
+}
+
+impl Snapshot {
+    /// Version of the snapshot format itself, independent from the versions of the modules
+    pub const FORMAT: u32 = 1;
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Snapshots are always serializable")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let snapshot: Self = serde_json::from_slice(bytes).map_err(SnapshotError::Corrupted)?;
+        if snapshot.format != Self::FORMAT {
+            return Err(SnapshotError::UnsupportedFormat(snapshot.format));
+        }
+        Ok(snapshot)
+    }
+
+    /// Names of the modules contained in the snapshot
+    pub fn modules(&self) -> impl Iterator<Item = &str> {
+        self.modules.keys().map(String::as_str)
+    }
+}
+
+#[derive(Debug)]
+/// Error relative to snapshots
+pub enum SnapshotError {
+    /// Error occured because the snapshot could not be parsed
+    Corrupted(serde_json::Error),
+    /// Error occured because the snapshot was made with another format version, see [`Snapshot::FORMAT`]
+    UnsupportedFormat(u32),
+    /// Error occured because the snapshot does not contain a loaded snapshotted module
+    Missing(String),
+    /// Error occured because two snapshotted modules have the same [`SnapshotModule::NAME`]
+    DuplicateName(String),
+    /// Error occured because the snapshot of a module was made with another [`SnapshotModule::VERSION`]
+    VersionMismatch {
+        module: &'static str,
+        expected: u32,
+        found: u32,
+    },
+    /// Error occured while (de)serializing the state of a module
+    Serde {
+        module: &'static str,
+        source: serde_json::Error,
+    },
+    /// Error occured because the state of a module is currently borrowed
+    Borrow(BorrowError),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Corrupted(_) => write!(f, "The snapshot could not be parsed"),
+            Self::UnsupportedFormat(format) => write!(
+                f,
+                "The snapshot format {} is not supported, expected {}",
+                format,
+                Snapshot::FORMAT
+            ),
+            Self::Missing(module) => write!(f, "The snapshot does not contain {}", module),
+            Self::DuplicateName(name) => {
+                write!(f, "Several snapshotted modules are named {}", name)
+            }
+            Self::VersionMismatch {
+                module,
+                expected,
+                found,
+            } => write!(
+                f,
+                "The snapshot of {} has version {}, expected {}",
+                module, found, expected
+            ),
+            Self::Serde { module, .. } => {
+                write!(f, "Failed to (de)serialize the state of {}", module)
+            }
+            Self::Borrow(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Corrupted(e) | Self::Serde { source: e, .. } => Some(e),
+            Self::Borrow(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Type-erased [`SnapshotModule`] implementation of a module
+pub(crate) struct SnapshotHooks {
+    name: &'static str,
+    snapshot_name: &'static str,
+    version: u32,
+    snapshot: fn(&dyn Any) -> Result<Value, serde_json::Error>,
+    decode: fn(Value) -> Result<Box<dyn Any>, serde_json::Error>,
+    restore: fn(&mut dyn Any, Box<dyn Any>),
+}
+
+impl SnapshotHooks {
+    fn new<T: SnapshotModule>() -> Self {
+        Self {
+            name: type_name::<T>(),
+            snapshot_name: T::NAME,
+            version: T::VERSION,
+            snapshot: |state| serde_json::to_value(state.downcast_ref::<T>().unwrap().snapshot()),
+            decode: |value| Ok(Box::new(serde_json::from_value::<T::Snapshot>(value)?)),
+            restore: |state, snapshot| {
+                state
+                    .downcast_mut::<T>()
+                    .unwrap()
+                    .restore(*snapshot.downcast::<T::Snapshot>().unwrap())
+            },
+        }
+    }
+
+    /// Key of the instance `id` in the snapshots
+    fn key(&self, id: &ModuleId) -> String {
+        match id.key() {
+            Some(key) => format!("{}[{}]", self.snapshot_name, key),
+            None => self.snapshot_name.to_owned(),
+        }
+    }
+}
+
+impl Engine {
+    /// Includes the module `T` in the snapshots of this engine, see [`SnapshotModule`].
+    ///
+    /// The registration is shared by the instances of `T`, and dropped when the last one is unloaded.
+    pub fn enable_snapshot<T: SnapshotModule>(&mut self) {
+        self.snapshots
+            .insert(TypeId::of::<T>(), SnapshotHooks::new::<T>());
+    }
+
+    /// Captures the state of every loaded snapshotted module.
+    pub fn snapshot(&self) -> Result<Snapshot, SnapshotError> {
+        let mut modules = BTreeMap::new();
+        for id in &self.load_order {
+            let Some(hooks) = self.snapshots.get(&id.type_id) else {
+                continue;
+            };
+            let state = self.modules[id]
+                .state
+                .try_borrow(None)
+                .map_err(SnapshotError::Borrow)?;
+            let state = (hooks.snapshot)(&**state).map_err(|source| SnapshotError::Serde {
+                module: hooks.name,
+                source,
+            })?;
+            let key = hooks.key(id);
+            if modules.contains_key(&key) {
+                return Err(SnapshotError::DuplicateName(key));
+            }
+            modules.insert(
+                key,
+                ModuleSnapshot {
+                    version: hooks.version,
+                    state,
+                },
+            );
+        }
+        Ok(Snapshot {
+            format: Snapshot::FORMAT,
+            modules,
+        })
+    }
+
+    /// Restores the state of every loaded snapshotted module, in their loading order.
+    ///
+    /// Every module state is decoded before any is restored, so that on error the engine is left untouched.
+    /// Modules of the snapshot that are not loaded or not snapshotted are ignored.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), SnapshotError> {
+        let mut decoded = Vec::new();
+        for id in &self.load_order {
+            let Some(hooks) = self.snapshots.get(&id.type_id) else {
+                continue;
+            };
+            let key = hooks.key(id);
+            if decoded
+                .iter()
+                .any(|(other, other_hooks, _): &(&ModuleId, &SnapshotHooks, _)| {
+                    other_hooks.key(other) == key
+                })
+            {
+                return Err(SnapshotError::DuplicateName(key));
+            }
+            let module = snapshot
+                .modules
+                .get(&key)
+                .ok_or(SnapshotError::Missing(key))?;
+            if module.version != hooks.version {
+                return Err(SnapshotError::VersionMismatch {
+                    module: hooks.name,
+                    expected: hooks.version,
+                    found: module.version,
+                });
+            }
+            let state =
+                (hooks.decode)(module.state.clone()).map_err(|source| SnapshotError::Serde {
+                    module: hooks.name,
+                    source,
+                })?;
+            decoded.push((id, hooks, state));
+        }
+
+        let mut states = Vec::new();
+        for (id, _, _) in &decoded {
+            let state = &self.modules[*id].state;
+            states.push(state.try_borrow_mut(None).map_err(SnapshotError::Borrow)?);
+        }
+        for ((_, hooks, state), mut module) in decoded.into_iter().zip(states) {
+            (hooks.restore)(&mut **module, state);
+        }
+        Ok(())
+    }
+}
@@ -1,2 +0,0 @@
-pub struct StartEvent;
-pub struct ShutdownEvent;
@@ -0,0 +1,173 @@
+use std::time::{Duration, Instant};
+
+use rgine_logger::warn;
+
+use crate::{
+    events::{EventQueue, Listener},
+    AnyResult, Engine, Module,
+};
+
+use super::PlatformUpdateEvent;
+
+/// Emitted by the [`ClockModule`] once for every fixed timestep that elapsed.
+///
+/// Gameplay logic should listen to this event as it runs at a constant rate, independently of the framerate.
+pub struct TickedUpdate {
+    /// Number of ticks emitted before this one
+    pub tick: u64,
+    /// Fixed duration of a tick
+    pub dt: Duration,
+}
+
+/// Emitted by the [`ClockModule`] once every platform iteration.
+pub struct RealTimeUpdate {
+    /// Time elapsed since the last platform iteration, scaled by the clock time scale (zero while paused)
+    pub dt: Duration,
+    /// Total scaled time elapsed since the clock started
+    pub elapsed: Duration,
+}
+
+/// Controls of the [`ClockModule`]
+pub enum ClockEvent {
+    Pause,
+    Resume,
+    /// Multiplies every elapsed duration by the given factor (`1.0` being real time).
+    ///
+    /// Negative or non-finite factors are ignored.
+    SetTimeScale(f64),
+    /// Sets the fixed duration between two [`TickedUpdate`], a zero timestep is ignored.
+    SetTimestep(Duration),
+    /// Sets how many [`TickedUpdate`] can be emitted in a single platform iteration to catch up.
+    ///
+    /// Any time left over past this limit is dropped to avoid spiraling when updates are slower than the timestep.
+    SetMaxTicksPerFrame(u32),
+    /// Uses the given duration as the time elapsed between two platform iterations instead of measuring it,
+    /// which makes the clock deterministic. `None` goes back to measuring real time.
+    SetFixedDelta(Option<Duration>),
+}
+
+/// Keeps track of time and emits [`TickedUpdate`] and [`RealTimeUpdate`] on every [`PlatformUpdateEvent`].
+pub struct ClockModule {
+    last_update: Option<Instant>,
+    fixed_delta: Option<Duration>,
+    accumulator: Duration,
+    elapsed: Duration,
+    tick: u64,
+
+    paused: bool,
+    time_scale: f64,
+    timestep: Duration,
+    max_ticks_per_frame: u32,
+}
+
+impl ClockModule {
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    pub fn max_ticks_per_frame(&self) -> u32 {
+        self.max_ticks_per_frame
+    }
+
+    /// Progress towards the next tick in `[0; 1[`, useful to interpolate between two ticked states.
+    pub fn alpha(&self) -> f64 {
+        self.accumulator.as_secs_f64() / self.timestep.as_secs_f64()
+    }
+}
+
+impl Module for ClockModule {
+    type ListeningTo = (PlatformUpdateEvent, ClockEvent);
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self {
+            last_update: None,
+            fixed_delta: None,
+            accumulator: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            tick: 0,
+
+            paused: false,
+            time_scale: 1.,
+            timestep: Duration::from_secs(1) / 60,
+            max_ticks_per_frame: 8,
+        })
+    }
+}
+
+impl Listener<PlatformUpdateEvent> for ClockModule {
+    fn on_event(&mut self, _: &mut PlatformUpdateEvent, queue: &mut EventQueue) {
+        let now = Instant::now();
+        let last_update = self.last_update.replace(now);
+        let real_dt = self
+            .fixed_delta
+            .or(last_update.map(|last| now - last))
+            .unwrap_or_default();
+
+        let dt = if self.paused {
+            Duration::ZERO
+        } else {
+            // Saturates instead of panicking when a huge time scale overflows the duration
+            Duration::try_from_secs_f64(real_dt.as_secs_f64() * self.time_scale)
+                .unwrap_or(Duration::MAX)
+        };
+        self.elapsed = self.elapsed.saturating_add(dt);
+        self.accumulator = self.accumulator.saturating_add(dt);
+
+        let mut ticks = 0;
+        while self.accumulator >= self.timestep && ticks < self.max_ticks_per_frame {
+            queue.push(TickedUpdate {
+                tick: self.tick,
+                dt: self.timestep,
+            });
+            self.accumulator -= self.timestep;
+            self.tick += 1;
+            ticks += 1;
+        }
+        if self.accumulator >= self.timestep {
+            let leftover = self.accumulator.as_nanos() % self.timestep.as_nanos();
+            self.accumulator = Duration::from_nanos(leftover as u64);
+        }
+
+        queue.push(RealTimeUpdate {
+            dt,
+            elapsed: self.elapsed,
+        });
+    }
+}
+
+impl Listener<ClockEvent> for ClockModule {
+    fn on_event(&mut self, event: &mut ClockEvent, _: &mut EventQueue) {
+        match event {
+            ClockEvent::Pause => self.paused = true,
+            ClockEvent::Resume => self.paused = false,
+            ClockEvent::SetTimeScale(scale) if !scale.is_finite() || *scale < 0. => {
+                warn!(
+                    "Ignoring invalid clock time scale {}, keeping {}",
+                    scale, self.time_scale
+                )
+            }
+            ClockEvent::SetTimeScale(scale) => self.time_scale = *scale,
+            ClockEvent::SetTimestep(timestep) if timestep.is_zero() => {
+                warn!("Ignoring zero clock timestep, keeping {:?}", self.timestep)
+            }
+            ClockEvent::SetTimestep(timestep) => self.timestep = *timestep,
+            ClockEvent::SetMaxTicksPerFrame(max) => self.max_ticks_per_frame = *max,
+            ClockEvent::SetFixedDelta(delta) => self.fixed_delta = *delta,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod clock;
+
+pub use clock::{ClockEvent, ClockModule, RealTimeUpdate, TickedUpdate};
+
+pub struct StartEvent;
+pub struct ShutdownEvent;
+
+/// Dispatched by the platform layer once every iteration of its main loop,
+/// right after the deferred events due this iteration and the outputs of the completed tasks
+/// (see [`Engine::flush_deferred_events`](crate::Engine::flush_deferred_events)).
+pub struct PlatformUpdateEvent;
@@ -0,0 +1,161 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::JoinHandle,
+};
+
+use crate::{events::Event, Engine};
+
+pub(crate) type Task = Pin<Box<dyn Future<Output = Box<dyn Event>>>>;
+
+/// Runs the futures spawned with [`EventQueue::spawn`](crate::events::EventQueue::spawn) on the main thread,
+/// their output being dispatched as an event.
+///
+/// Tasks are polled by [`Engine::flush_deferred_events`], once every platform update, if they have been woken.
+/// Blocking work should be moved off the main thread with [`unblock`].
+#[derive(Default)]
+pub struct EngineExecutor {
+    tasks: Vec<(Task, Arc<TaskWaker>)>,
+}
+
+struct TaskWaker {
+    woken: AtomicBool,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release)
+    }
+}
+
+impl EngineExecutor {
+    pub(crate) fn spawn(&mut self, task: Task) {
+        let waker = Arc::new(TaskWaker {
+            woken: AtomicBool::new(true),
+        });
+        self.tasks.push((task, waker));
+    }
+
+    /// Polls the woken tasks, returns the outputs of the completed ones in spawn order.
+    pub(crate) fn poll(&mut self) -> Vec<Box<dyn Event>> {
+        let mut outputs = Vec::new();
+        self.tasks.retain_mut(|(task, waker)| {
+            if !waker.woken.swap(false, Ordering::Acquire) {
+                return true;
+            }
+            let waker = Waker::from(waker.clone());
+            match task.as_mut().poll(&mut Context::from_waker(&waker)) {
+                Poll::Ready(output) => {
+                    outputs.push(output);
+                    false
+                }
+                Poll::Pending => true,
+            }
+        });
+        outputs
+    }
+
+    /// Number of tasks not completed yet
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
+
+/// Runs `f` on a new thread, the returned future completes with its result.
+///
+/// If `f` panics, the panic is resumed when the future is polled.
+pub fn unblock<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Unblock<T> {
+    let shared = Arc::new(Mutex::new(Shared {
+        value: None,
+        waker: None,
+    }));
+    let thread_shared = shared.clone();
+    let thread = std::thread::spawn(move || {
+        // Wakes the task even if `f` panics, so that the panic gets resumed
+        let _wake = WakeOnDrop(thread_shared.clone());
+        let value = f();
+        thread_shared.lock().unwrap().value = Some(value);
+    });
+    Unblock {
+        shared,
+        thread: Some(thread),
+    }
+}
+
+/// Future returned by [`unblock`]
+pub struct Unblock<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Shared<T> {
+    value: Option<T>,
+    waker: Option<Waker>,
+}
+
+struct WakeOnDrop<T>(Arc<Mutex<Shared<T>>>);
+
+impl<T> Drop for WakeOnDrop<T> {
+    fn drop(&mut self) {
+        let waker = self
+            .0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .waker
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for Unblock<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(value) = shared.value.take() {
+            return Poll::Ready(value);
+        }
+        shared.waker = Some(cx.waker().clone());
+        drop(shared);
+
+        if self.thread.as_ref().is_some_and(JoinHandle::is_finished) {
+            // The thread panicked before sending its result
+            if let Err(panic) = self.thread.take().unwrap().join() {
+                std::panic::resume_unwind(panic);
+            }
+            // The result was sent after the lock was released
+            cx.waker().wake_by_ref();
+        }
+        Poll::Pending
+    }
+}
+
+impl Engine {
+    /// Spawns `future` on the [`EngineExecutor`], its output is dispatched as an event once it completes.
+    pub fn spawn<E: Event>(&mut self, future: impl Future<Output = E> + 'static) {
+        self.executor.spawn(task(future));
+    }
+
+    pub fn executor(&self) -> &EngineExecutor {
+        &self.executor
+    }
+}
+
+pub(crate) fn task<E: Event>(future: impl Future<Output = E> + 'static) -> Task {
+    Box::pin(async move { Box::new(future.await) as Box<dyn Event> })
+}
@@ -0,0 +1,60 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use crate::events::Event;
+
+/// When a deferred event should be dispatched
+pub(crate) enum Delay {
+    /// After the given number of platform updates
+    Updates(usize),
+    /// At the first platform update past the deadline
+    Until(Instant),
+}
+
+impl Delay {
+    pub(crate) fn after(duration: Duration) -> Self {
+        Self::Until(Instant::now() + duration)
+    }
+}
+
+/// Holds the deferred events until they are due.
+///
+/// Events delayed by a number of platform updates are keyed by the update they are due at,
+/// events delayed by a duration by their deadline, so that far away delays don't cost more than close ones.
+#[derive(Default)]
+pub(crate) struct Timers {
+    /// Number of platform updates so far
+    update: u64,
+    by_update: BTreeMap<u64, Vec<Box<dyn Event>>>,
+    by_deadline: BTreeMap<Instant, Vec<Box<dyn Event>>>,
+}
+
+impl Timers {
+    pub(crate) fn schedule(&mut self, delay: Delay, event: Box<dyn Event>) {
+        match delay {
+            Delay::Updates(updates) => {
+                let due = self.update.saturating_add(updates.max(1) as u64);
+                self.by_update.entry(due).or_default().push(event);
+            }
+            Delay::Until(deadline) => self.by_deadline.entry(deadline).or_default().push(event),
+        }
+    }
+
+    /// Advances by one platform update and returns the events that are now due, in scheduling order.
+    pub(crate) fn advance(&mut self) -> Vec<Box<dyn Event>> {
+        self.update += 1;
+        let mut due = self.by_update.remove(&self.update).unwrap_or_default();
+
+        let now = Instant::now();
+        while let Some(entry) = self.by_deadline.first_entry() {
+            if *entry.key() > now {
+                break;
+            }
+            due.extend(entry.remove());
+        }
+
+        due
+    }
+}
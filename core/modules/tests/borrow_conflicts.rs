@@ -0,0 +1,82 @@
+use std::any::type_name;
+
+use rgine_modules::{AnyResult, Dependency, DependencyMut, Engine, Module};
+
+#[derive(Default)]
+struct Target {
+    value: u32,
+}
+
+/// Declares that it writes to `Target`
+struct Writer {
+    target: DependencyMut<Target>,
+}
+
+struct Reader {
+    target: Dependency<Target>,
+}
+
+impl Module for Target {
+    type ListeningTo = ();
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+}
+
+impl Module for Writer {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        Ok(Self {
+            target: ctx.dependency_mut()?,
+        })
+    }
+}
+
+impl Module for Reader {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        Ok(Self {
+            target: ctx.dependency()?,
+        })
+    }
+}
+
+fn engine() -> (Dependency<Writer>, Dependency<Reader>) {
+    let mut engine = Engine::new_without_logger::<Writer>();
+    let writer = engine.dependency::<Writer>().unwrap();
+    let reader = engine.dependency::<Reader>().unwrap();
+    (writer, reader)
+}
+
+#[test]
+fn reading_while_written_names_both_modules() {
+    let (writer, reader) = engine();
+    let writer = writer.read_state();
+    let mut target = writer.target.write_state();
+    target.value = 1;
+
+    let reader = reader.read_state();
+    let error = reader.target.try_read_state().err().unwrap();
+    assert_eq!(error.module, type_name::<Target>());
+    assert_eq!(error.requested_by, Some(type_name::<Reader>()));
+    assert_eq!(error.borrowed_by, Some(type_name::<Writer>()));
+
+    drop(target);
+    assert_eq!(reader.target.try_read_state().unwrap().value, 1);
+}
+
+#[test]
+fn writing_while_read_fails() {
+    let (writer, reader) = engine();
+    let reader = reader.read_state();
+    let _target = reader.target.read_state();
+
+    let writer = writer.read_state();
+    let error = writer.target.try_write_state().err().unwrap();
+    assert_eq!(error.module, type_name::<Target>());
+    assert_eq!(error.requested_by, Some(type_name::<Writer>()));
+    assert_eq!(error.borrowed_by, None);
+}
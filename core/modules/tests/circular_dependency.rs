@@ -0,0 +1,81 @@
+use std::any::type_name;
+
+use rgine_modules::{AnyResult, Engine, Module, ModuleError};
+
+struct A;
+struct B;
+/// Depends on itself
+struct Own;
+struct Leaf;
+
+impl Module for A {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        ctx.dependency::<B>()?;
+        Ok(Self)
+    }
+}
+
+impl Module for B {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        ctx.dependency::<A>()?;
+        Ok(Self)
+    }
+}
+
+impl Module for Own {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        ctx.dependency::<Own>()?;
+        Ok(Self)
+    }
+}
+
+impl Module for Leaf {
+    type ListeningTo = ();
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self)
+    }
+}
+
+#[test]
+fn cycle_reports_the_full_chain() {
+    let mut engine = Engine::new_without_logger::<Leaf>();
+    match engine.dependency::<A>() {
+        Err(ModuleError::CircularDependency(chain)) => assert_eq!(
+            chain,
+            [type_name::<A>(), type_name::<B>(), type_name::<A>()]
+        ),
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("the cycle was not detected"),
+    }
+}
+
+#[test]
+fn self_dependency_is_a_cycle() {
+    let mut engine = Engine::new_without_logger::<Leaf>();
+    match engine.dependency::<Own>() {
+        Err(ModuleError::CircularDependency(chain)) => {
+            assert_eq!(chain, [type_name::<Own>(), type_name::<Own>()])
+        }
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("the cycle was not detected"),
+    }
+}
+
+#[test]
+fn engine_is_usable_after_a_cycle() {
+    let mut engine = Engine::new_without_logger::<Leaf>();
+    assert!(matches!(
+        engine.dependency::<A>(),
+        Err(ModuleError::CircularDependency(_))
+    ));
+    assert!(!engine.is_loaded::<A>());
+    assert!(!engine.is_loaded::<B>());
+    assert!(engine.dependency::<Leaf>().is_ok());
+}
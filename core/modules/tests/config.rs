@@ -0,0 +1,195 @@
+#![cfg(feature = "config")]
+
+use std::path::PathBuf;
+
+use rgine_modules::{
+    config::{Config, ConfigError, ModuleConfig},
+    AnyResult, Engine, Module,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+struct WindowConfig {
+    title: String,
+    size: Size,
+    vsync: bool,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+struct Size {
+    width: u32,
+    height: u32,
+}
+
+impl ModuleConfig for WindowConfig {
+    const SECTION: &'static str = "window";
+
+    fn validate(&self) -> Result<(), String> {
+        match self.size.width {
+            0..=4096 => Ok(()),
+            width => Err(format!("width {width} is too large")),
+        }
+    }
+}
+
+fn window(title: &str, width: u32, height: u32, vsync: bool) -> WindowConfig {
+    WindowConfig {
+        title: title.to_owned(),
+        size: Size { width, height },
+        vsync,
+    }
+}
+
+fn temp_file(name: &str, text: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rgine_config_{}_{name}", std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn toml_and_ron_files_load_the_same_config() {
+    let toml = temp_file(
+        "window.toml",
+        r#"
+[window]
+title = "Game"
+size = { width = 800, height = 600 }
+"#,
+    );
+    let ron = temp_file(
+        "window.ron",
+        r#"(
+    window: (title: "Game", size: (width: 800, height: 600)),
+)"#,
+    );
+
+    let from_toml = Config::load(&toml);
+    let from_ron = Config::load(&ron);
+    std::fs::remove_file(toml).unwrap();
+    std::fs::remove_file(ron).unwrap();
+
+    let (from_toml, from_ron) = (from_toml.unwrap(), from_ron.unwrap());
+    assert_eq!(from_toml, from_ron);
+    assert_eq!(from_toml.sections().collect::<Vec<_>>(), ["window"]);
+    assert_eq!(
+        from_toml.get::<WindowConfig>().unwrap(),
+        window("Game", 800, 600, false)
+    );
+}
+
+#[test]
+fn unsupported_and_invalid_files_are_rejected() {
+    assert!(matches!(
+        Config::load("config.json"),
+        Err(ConfigError::UnsupportedFormat(_))
+    ));
+
+    let path = temp_file("invalid.toml", "[window");
+    let config = Config::load(&path);
+    std::fs::remove_file(path).unwrap();
+    assert!(matches!(config, Err(ConfigError::Parse { .. })));
+}
+
+#[test]
+fn overrides_set_nested_values() {
+    let config = Config::new()
+        .with_overrides([
+            "window.title = Game",
+            "window.size.width=1280",
+            "window.size.height=720",
+            "window.vsync=true",
+        ])
+        .unwrap();
+    assert_eq!(
+        config.get::<WindowConfig>().unwrap(),
+        window("Game", 1280, 720, true)
+    );
+
+    // Later overrides win
+    let config = config
+        .with_overrides(["window.title=\"Other\"", "window.size.width=640"])
+        .unwrap();
+    assert_eq!(
+        config.get::<WindowConfig>().unwrap(),
+        window("Other", 640, 720, true)
+    );
+}
+
+#[test]
+fn environment_overrides_the_config() {
+    // No other test of this binary reads the environment
+    std::env::set_var("RGINE__WINDOW__SIZE__HEIGHT", "480");
+    let config = Config::new()
+        .with_overrides(["window.size.height=720"])
+        .unwrap()
+        .with_env()
+        .unwrap();
+    assert_eq!(config.get::<WindowConfig>().unwrap().size.height, 480);
+}
+
+#[test]
+fn malformed_overrides_are_rejected() {
+    for entry in ["window", "title=Game", "window.=Game", "window.title.x=1"] {
+        let config = Config::new().with_overrides(["window.title=Game", entry]);
+        assert!(
+            matches!(config, Err(ConfigError::Override { ref value, .. }) if value == entry),
+            "{entry}"
+        );
+    }
+}
+
+#[test]
+fn sections_are_checked() {
+    let config = Config::new().with_overrides(["window.size.width=8192"]);
+    assert!(matches!(
+        config.unwrap().get::<WindowConfig>(),
+        Err(ConfigError::Validation {
+            section: "window",
+            ..
+        })
+    ));
+
+    let config = Config::new().with_overrides(["window.vsync=[1, 2]"]);
+    assert!(matches!(
+        config.unwrap().get::<WindowConfig>(),
+        Err(ConfigError::Invalid {
+            section: "window",
+            ..
+        })
+    ));
+
+    // Missing sections default
+    assert_eq!(
+        Config::new().get::<WindowConfig>().unwrap(),
+        WindowConfig::default()
+    );
+}
+
+struct Window {
+    config: WindowConfig,
+}
+
+impl Module for Window {
+    type ListeningTo = ();
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        Ok(Self {
+            config: engine.config()?,
+        })
+    }
+}
+
+#[test]
+fn modules_read_their_section() {
+    let config = Config::new().with_overrides(["window.title=Game"]).unwrap();
+    let mut engine = Engine::try_with_config::<Window>(config).unwrap();
+    let window = engine.dependency::<Window>().unwrap();
+    assert_eq!(window.read_state().config.title, "Game");
+
+    let config = Config::new()
+        .with_overrides(["window.size.width=8192"])
+        .unwrap();
+    assert!(Engine::try_with_config::<Window>(config).is_err());
+}
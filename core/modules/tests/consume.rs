@@ -0,0 +1,73 @@
+use std::cell::RefCell;
+
+use rgine_modules::{
+    events::{EventQueue, Listener, ListenerOrder},
+    AnyResult, Engine, Module,
+};
+
+/// Swallowed by `Ui` when `0` is true
+struct Click(bool);
+
+thread_local! {
+    /// Names of the modules in the order they received the clicks
+    static RECEIVED: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+fn received() -> Vec<&'static str> {
+    RECEIVED.with_borrow_mut(std::mem::take)
+}
+
+struct Ui;
+struct Gameplay;
+
+impl Module for Ui {
+    type ListeningTo = (Click,);
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        ctx.dependency::<Gameplay>()?;
+        Ok(Self)
+    }
+}
+
+impl Module for Gameplay {
+    type ListeningTo = (Click,);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self)
+    }
+}
+
+impl Listener<Click> for Ui {
+    fn on_event(&mut self, event: &mut Click, queue: &mut EventQueue) {
+        RECEIVED.with_borrow_mut(|received| received.push("Ui"));
+        if event.0 {
+            queue.consume();
+        }
+    }
+
+    fn order() -> ListenerOrder {
+        ListenerOrder::new().before::<Gameplay>()
+    }
+}
+
+impl Listener<Click> for Gameplay {
+    fn on_event(&mut self, _: &mut Click, queue: &mut EventQueue) {
+        assert!(!queue.is_consumed());
+        RECEIVED.with_borrow_mut(|received| received.push("Gameplay"));
+    }
+}
+
+#[test]
+fn consumed_events_skip_later_listeners() {
+    let mut engine = Engine::new_without_logger::<Ui>();
+
+    engine.run_with(Click(false));
+    assert_eq!(received(), ["Ui", "Gameplay"]);
+
+    engine.run_with(Click(true));
+    assert_eq!(received(), ["Ui"]);
+
+    // Only the consumed event is skipped
+    engine.run_with(Click(false));
+    assert_eq!(received(), ["Ui", "Gameplay"]);
+}
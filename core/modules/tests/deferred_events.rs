@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use rgine_modules::{
+    events::{EventQueue, Listener},
+    AnyResult, Engine, Module,
+};
+
+/// Schedules `Fired(name)` events with the delays of the test
+enum Schedule {
+    Ticks(usize),
+    NextUpdate,
+    After(Duration),
+}
+struct Fired(&'static str);
+
+#[derive(Default)]
+struct Timers {
+    fired: Vec<&'static str>,
+}
+
+impl Module for Timers {
+    type ListeningTo = (Schedule, Fired);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+}
+
+impl Listener<Schedule> for Timers {
+    fn on_event(&mut self, event: &mut Schedule, queue: &mut EventQueue) {
+        match *event {
+            Schedule::Ticks(ticks) => queue.push_after_ticks(ticks, Fired("ticks")),
+            Schedule::NextUpdate => queue.push_next_update(Fired("next update")),
+            Schedule::After(delay) => queue.push_after(delay, Fired("after")),
+        }
+    }
+}
+
+impl Listener<Fired> for Timers {
+    fn on_event(&mut self, event: &mut Fired, _: &mut EventQueue) {
+        self.fired.push(event.0);
+    }
+}
+
+/// Flushes `updates` platform updates and returns the events fired during the last one
+fn fired_after(engine: &mut Engine, updates: usize) -> Vec<&'static str> {
+    for _ in 1..updates {
+        engine.flush_deferred_events();
+    }
+    engine
+        .dependency_mut::<Timers>()
+        .unwrap()
+        .write_state()
+        .fired
+        .clear();
+    engine.flush_deferred_events();
+    let fired = engine
+        .dependency::<Timers>()
+        .unwrap()
+        .read_state()
+        .fired
+        .clone();
+    fired
+}
+
+#[test]
+fn ticks_delay_exactly_n_updates() {
+    let mut engine = Engine::new_without_logger::<Timers>();
+    engine.run_with(Schedule::Ticks(3));
+    engine.run_with(Schedule::NextUpdate);
+
+    assert_eq!(fired_after(&mut engine, 1), ["next update"]);
+    assert_eq!(fired_after(&mut engine, 1), Vec::<&str>::new());
+    assert_eq!(fired_after(&mut engine, 1), ["ticks"]);
+    assert_eq!(fired_after(&mut engine, 5), Vec::<&str>::new());
+}
+
+#[test]
+fn zero_ticks_waits_for_the_next_update() {
+    let mut engine = Engine::new_without_logger::<Timers>();
+    engine.run_with(Schedule::Ticks(0));
+    assert_eq!(fired_after(&mut engine, 1), ["ticks"]);
+}
+
+#[test]
+fn far_away_delays_are_cheap() {
+    let mut engine = Engine::new_without_logger::<Timers>();
+    engine.run_with(Schedule::Ticks(usize::MAX));
+    engine.run_with(Schedule::After(Duration::from_secs(3600)));
+    assert_eq!(fired_after(&mut engine, 10), Vec::<&str>::new());
+}
+
+#[test]
+fn elapsed_durations_fire_on_the_next_update() {
+    let mut engine = Engine::new_without_logger::<Timers>();
+    engine.run_with(Schedule::After(Duration::ZERO));
+    assert_eq!(fired_after(&mut engine, 1), ["after"]);
+}
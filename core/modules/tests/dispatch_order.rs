@@ -0,0 +1,107 @@
+use rgine_modules::{
+    events::{DispatchOrder, DispatchPolicy, EventQueue, Listener},
+    AnyResult, Engine, Module,
+};
+
+/// Event whose children are pushed as `Ev`, except for `"wide"` which is pushed as `Wide`
+struct Ev(&'static str);
+struct Wide(&'static str);
+
+#[derive(Default)]
+struct Recorder {
+    dispatched: Vec<&'static str>,
+}
+
+impl Recorder {
+    /// root -> [wide, b], wide -> [c1, c2], c1 -> [c1x], b -> [b1]
+    fn on(&mut self, name: &'static str, queue: &mut EventQueue) {
+        self.dispatched.push(name);
+        match name {
+            "root" => {
+                queue.push(Wide("wide"));
+                queue.push(Ev("b"));
+            }
+            "wide" => {
+                queue.push(Ev("c1"));
+                queue.push(Ev("c2"));
+            }
+            "c1" => queue.push(Ev("c1x")),
+            "b" => queue.push(Ev("b1")),
+            _ => {}
+        }
+    }
+}
+
+impl Module for Recorder {
+    type ListeningTo = (Ev, Wide);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+}
+
+impl Listener<Ev> for Recorder {
+    fn on_event(&mut self, event: &mut Ev, queue: &mut EventQueue) {
+        self.on(event.0, queue)
+    }
+}
+
+impl Listener<Wide> for Recorder {
+    fn on_event(&mut self, event: &mut Wide, queue: &mut EventQueue) {
+        self.on(event.0, queue)
+    }
+}
+
+fn dispatch(policy: Option<DispatchPolicy>) -> Vec<&'static str> {
+    let mut engine = Engine::new_without_logger::<Recorder>();
+    if let Some(policy) = policy {
+        engine.set_dispatch_policy(policy);
+    }
+    engine.run_with(Ev("root"));
+    let recorder = engine.dependency::<Recorder>().unwrap();
+    let dispatched = recorder.read_state().dispatched.clone();
+    dispatched
+}
+
+#[test]
+fn depth_first_by_default() {
+    assert_eq!(
+        dispatch(None),
+        ["root", "wide", "c1", "c1x", "c2", "b", "b1"]
+    );
+}
+
+#[test]
+fn depth_first() {
+    assert_eq!(
+        dispatch(Some(DispatchPolicy::new(DispatchOrder::DepthFirst))),
+        ["root", "wide", "c1", "c1x", "c2", "b", "b1"]
+    );
+}
+
+#[test]
+fn breadth_first() {
+    assert_eq!(
+        dispatch(Some(DispatchPolicy::new(DispatchOrder::BreadthFirst))),
+        ["root", "wide", "b", "c1", "c2", "b1", "c1x"]
+    );
+}
+
+#[test]
+fn per_event_type() {
+    // Only the children of `Wide` wait for the events already scheduled
+    assert_eq!(
+        dispatch(Some(
+            DispatchPolicy::new(DispatchOrder::DepthFirst)
+                .with::<Wide>(DispatchOrder::BreadthFirst)
+        )),
+        ["root", "wide", "b", "b1", "c1", "c1x", "c2"]
+    );
+    // Only the children of `Ev` jump ahead of the events already scheduled
+    assert_eq!(
+        dispatch(Some(
+            DispatchPolicy::new(DispatchOrder::BreadthFirst).with::<Ev>(DispatchOrder::DepthFirst)
+        )),
+        ["root", "wide", "b", "b1", "c1", "c1x", "c2"]
+    );
+}
@@ -0,0 +1,87 @@
+use rgine_modules::{
+    events::{EventQueue, Listener, Listeners},
+    AnyResult, Engine, Module,
+};
+
+struct Tick;
+struct Collision;
+
+struct Physics;
+
+impl Module for Physics {
+    type ListeningTo = (Tick,);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self)
+    }
+
+    fn listeners(listeners: &mut Listeners<Self>) {
+        listeners.emits::<Collision>();
+    }
+}
+
+impl Listener<Tick> for Physics {
+    fn on_event(&mut self, _: &mut Tick, queue: &mut EventQueue) {
+        queue.push(Collision);
+    }
+}
+
+struct Game;
+
+impl Module for Game {
+    type ListeningTo = (Collision,);
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.dependency::<Physics>()?;
+        Ok(Self)
+    }
+}
+
+impl Listener<Collision> for Game {
+    fn on_event(&mut self, _: &mut Collision, _: &mut EventQueue) {}
+}
+
+#[test]
+fn module_graph_dot() {
+    let engine = Engine::new_without_logger::<Game>();
+    assert_eq!(
+        engine.module_graph_dot(),
+        r#"digraph modules {
+    "graph::Physics" [label="Physics (inside of graph)", shape=box];
+    "graph::Game" [label="Game (inside of graph)", shape=box];
+    "graph::Game" -> "graph::Physics";
+}
+"#
+    );
+}
+
+#[test]
+fn event_graph_dot() {
+    let engine = Engine::new_without_logger::<Game>();
+    assert_eq!(
+        engine.event_graph_dot(),
+        r#"digraph events {
+    "graph::Physics" [label="Physics (inside of graph)", shape=box];
+    "graph::Game" [label="Game (inside of graph)", shape=box];
+    "graph::Collision" [label="Collision (inside of graph)", shape=ellipse];
+    "graph::Tick" [label="Tick (inside of graph)", shape=ellipse];
+    "graph::Physics" -> "graph::Collision";
+    "graph::Tick" -> "graph::Physics";
+    "graph::Collision" -> "graph::Game";
+}
+"#
+    );
+}
+
+#[test]
+fn module_graph_json() {
+    let engine = Engine::new_without_logger::<Game>();
+    assert_eq!(
+        engine.module_graph_json(),
+        r#"{ "modules": [
+    { "name": "graph::Physics", "key": null, "dependencies": [], "events": ["graph::Tick"], "emits": ["graph::Collision"] },
+    { "name": "graph::Game", "key": null, "dependencies": ["graph::Physics"], "events": ["graph::Collision"], "emits": [] }
+] }
+"#
+    );
+}
@@ -0,0 +1,83 @@
+use std::any::type_name;
+
+use rgine_modules::{AnyResult, Engine, Module, ModuleError};
+
+struct Game;
+struct Renderer;
+/// Fails to initialize
+struct Window;
+struct Leaf;
+
+impl Module for Game {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        ctx.dependency::<Renderer>()?;
+        Ok(Self)
+    }
+}
+
+impl Module for Renderer {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        ctx.dependency::<Window>()?;
+        Ok(Self)
+    }
+}
+
+impl Module for Window {
+    type ListeningTo = ();
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Err("no display".into())
+    }
+}
+
+impl Module for Leaf {
+    type ListeningTo = ();
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self)
+    }
+}
+
+#[test]
+fn nested_failures_report_the_loading_path() {
+    let Err(error) = Engine::try_new_without_logger::<Game>() else {
+        panic!("the engine should fail to load");
+    };
+    let ModuleError::InitError {
+        module,
+        path,
+        source,
+    } = &error
+    else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(*module, type_name::<Window>());
+    assert_eq!(
+        path,
+        &[
+            type_name::<Game>(),
+            type_name::<Renderer>(),
+            type_name::<Window>()
+        ]
+    );
+    assert_eq!(source.to_string(), "no display");
+    assert_eq!(
+        error.to_string(),
+        "Failed to initialize Window (inside of init_error), loaded through: \
+        Game (inside of init_error) -> Renderer (inside of init_error) -> Window (inside of init_error)"
+    );
+}
+
+#[test]
+fn direct_failures_have_a_single_module_path() {
+    let mut engine = Engine::try_new_without_logger::<Leaf>().unwrap();
+    let Err(ModuleError::InitError { path, .. }) = engine.dependency::<Window>() else {
+        panic!("Window should fail to load");
+    };
+    assert_eq!(path, [type_name::<Window>()]);
+    assert!(!engine.is_loaded::<Window>());
+}
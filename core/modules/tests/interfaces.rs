@@ -0,0 +1,80 @@
+use rgine_modules::{AnyResult, Engine, Module, ModuleError};
+
+trait Renderer {
+    fn name(&self) -> &'static str;
+}
+
+struct Gl;
+
+impl Module for Gl {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        ctx.provide::<Self, dyn Renderer>(|m| m, |m| m);
+        Ok(Self)
+    }
+}
+
+impl Renderer for Gl {
+    fn name(&self) -> &'static str {
+        "gl"
+    }
+}
+
+/// Provides `Renderer` but fails to initialize afterwards
+struct Vulkan;
+
+impl Module for Vulkan {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        ctx.provide::<Self, dyn Renderer>(|m| m, |m| m);
+        Err("no vulkan driver".into())
+    }
+}
+
+impl Renderer for Vulkan {
+    fn name(&self) -> &'static str {
+        "vulkan"
+    }
+}
+
+struct Leaf;
+
+impl Module for Leaf {
+    type ListeningTo = ();
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self)
+    }
+}
+
+fn renderer(engine: &mut Engine) -> Result<&'static str, ModuleError> {
+    Ok(engine.interface::<dyn Renderer>()?.read_state().name())
+}
+
+#[test]
+fn interfaces_reach_their_provider() {
+    let mut engine = Engine::new_without_logger::<Leaf>();
+    assert!(matches!(renderer(&mut engine), Err(ModuleError::NotFound)));
+
+    engine.dependency::<Gl>().unwrap();
+    assert_eq!(renderer(&mut engine).unwrap(), "gl");
+
+    engine.unload_module::<Gl>().unwrap();
+    assert!(matches!(renderer(&mut engine), Err(ModuleError::NotFound)));
+}
+
+#[test]
+fn failed_providers_keep_the_current_one() {
+    let mut engine = Engine::new_without_logger::<Gl>();
+    assert!(engine.dependency::<Vulkan>().is_err());
+    assert_eq!(renderer(&mut engine).unwrap(), "gl");
+}
+
+#[test]
+fn failed_providers_are_not_registered() {
+    let mut engine = Engine::new_without_logger::<Leaf>();
+    assert!(engine.dependency::<Vulkan>().is_err());
+    assert!(matches!(renderer(&mut engine), Err(ModuleError::NotFound)));
+}
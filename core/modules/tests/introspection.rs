@@ -0,0 +1,108 @@
+use rgine_modules::{
+    events::{EventQueue, Listener},
+    introspection::{DebugFields, ModuleDebug},
+    AnyResult, Engine, Module, ModuleId,
+};
+
+struct Tick;
+
+#[derive(Default)]
+struct Clock {
+    ticks: u32,
+}
+
+impl Module for Clock {
+    type ListeningTo = (Tick,);
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.enable_debug::<Self>();
+        Ok(Self::default())
+    }
+}
+
+impl Listener<Tick> for Clock {
+    fn on_event(&mut self, _: &mut Tick, _: &mut EventQueue) {
+        self.ticks += 1;
+    }
+}
+
+impl ModuleDebug for Clock {
+    fn debug(&self, fields: &mut DebugFields) {
+        fields.field("ticks", self.ticks);
+    }
+}
+
+struct Game;
+
+impl Module for Game {
+    type ListeningTo = ();
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.dependency::<Clock>()?;
+        Ok(Self)
+    }
+}
+
+#[test]
+fn modules_describe_the_loaded_modules() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    engine.run_with(Tick);
+
+    let modules = engine.modules();
+    assert_eq!(
+        modules
+            .iter()
+            .map(|module| (module.id, module.load_index))
+            .collect::<Vec<_>>(),
+        [(ModuleId::of::<Clock>(), 0), (ModuleId::of::<Game>(), 1)]
+    );
+
+    let clock = &modules[0];
+    assert_eq!(clock.events, ["introspection::Tick"]);
+    assert!(clock.dependencies.is_empty());
+    assert_eq!(clock.dependents, [ModuleId::of::<Game>()]);
+    assert_eq!(
+        clock.state.as_ref().unwrap().iter().collect::<Vec<_>>(),
+        [("ticks", "1")]
+    );
+
+    let game = &modules[1];
+    assert!(game.events.is_empty());
+    assert_eq!(game.dependencies, [ModuleId::of::<Clock>()]);
+    assert!(game.dependents.is_empty());
+    assert!(game.state.is_none());
+}
+
+#[test]
+fn borrowed_states_are_not_exposed() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    let clock = engine.dependency_mut::<Clock>().unwrap();
+    let _state = clock.write_state();
+    assert!(engine.modules()[0].state.is_none());
+}
+
+#[test]
+fn dump_modules_lists_modules_in_load_order() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    engine.run_with(Tick);
+    engine.run_with(Tick);
+
+    let dump = engine.dump_modules();
+    let lines = dump
+        .lines()
+        // Init times vary between runs
+        .map(|line| line.split(" [init ").next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "2 loaded module(s):",
+            "0. Clock (inside of introspection)",
+            "    listens to: Tick (inside of introspection)",
+            "    depended on by: Game (inside of introspection)",
+            "    ticks = 2",
+            "1. Game (inside of introspection)",
+            "    depends on: Clock (inside of introspection)",
+        ]
+    );
+}
@@ -0,0 +1,156 @@
+use rgine_modules::{
+    events::{EventQueue, Listener, Query, Request},
+    AnyResult, Engine, Module, ModuleId,
+};
+
+struct Score(u32);
+/// Pushes `Score(1)` to the camera of player 2 only
+struct ScoreP2;
+/// Sends `Score(5)` to the unkeyed camera only
+struct Bonus;
+/// Makes the cameras query a `Zoom`
+struct Focus;
+
+/// Replied to with the score to add by `Lens`
+struct Zoom;
+
+impl Request for Zoom {
+    type Reply = Score;
+}
+
+/// One instance per player, keyed by the player name
+struct Camera {
+    player: Option<&'static str>,
+    score: u32,
+}
+
+impl Module for Camera {
+    type ListeningTo = (Score, Focus);
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        Ok(Self {
+            player: engine.loading_key(),
+            score: 0,
+        })
+    }
+}
+
+impl Listener<Score> for Camera {
+    fn on_event(&mut self, event: &mut Score, _: &mut EventQueue) {
+        self.score += event.0;
+    }
+}
+
+impl Listener<Focus> for Camera {
+    fn on_event(&mut self, _: &mut Focus, queue: &mut EventQueue) {
+        queue.query(Zoom);
+    }
+}
+
+struct Lens;
+
+impl Module for Lens {
+    type ListeningTo = (Query<Zoom>,);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self)
+    }
+}
+
+impl Listener<Query<Zoom>> for Lens {
+    fn on_event(&mut self, query: &mut Query<Zoom>, queue: &mut EventQueue) {
+        query.reply(queue, Score(10));
+    }
+}
+
+struct Game;
+
+impl Module for Game {
+    type ListeningTo = (ScoreP2, Bonus);
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.dependency::<Lens>()?;
+        for player in 1..=2 {
+            engine.dependency_with_key::<Camera>(&format!("p{player}"))?;
+        }
+        Ok(Self)
+    }
+}
+
+impl Listener<ScoreP2> for Game {
+    fn on_event(&mut self, _: &mut ScoreP2, queue: &mut EventQueue) {
+        queue.push_to(ModuleId::keyed::<Camera>("p2"), Score(1));
+    }
+}
+
+impl Listener<Bonus> for Game {
+    fn on_event(&mut self, _: &mut Bonus, queue: &mut EventQueue) {
+        queue.send_to::<Camera, _>(Score(5));
+    }
+}
+
+fn camera(engine: &mut Engine, key: Option<&str>) -> (Option<&'static str>, u32) {
+    let camera = match key {
+        Some(key) => engine.dependency_with_key::<Camera>(key),
+        None => engine.dependency::<Camera>(),
+    };
+    let camera = camera.unwrap();
+    let camera = camera.read_state();
+    (camera.player, camera.score)
+}
+
+#[test]
+fn keyed_instances_are_independent() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    assert!(engine.is_loaded_with_key::<Camera>("p1"));
+    assert!(engine.is_loaded_with_key::<Camera>(&String::from("p2")));
+    assert!(!engine.is_loaded::<Camera>());
+
+    engine.run_with(Score(2));
+    assert_eq!(camera(&mut engine, Some("p1")), (Some("p1"), 2));
+    assert_eq!(camera(&mut engine, Some("p2")), (Some("p2"), 2));
+
+    // Loaded by this call, after the event
+    assert_eq!(camera(&mut engine, None), (None, 0));
+}
+
+#[test]
+fn targeted_events_reach_a_single_instance() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    engine.run_with_target(ModuleId::keyed::<Camera>("p1"), Score(3));
+    engine.run_with(ScoreP2);
+    assert_eq!(camera(&mut engine, Some("p1")).1, 3);
+    assert_eq!(camera(&mut engine, Some("p2")).1, 1);
+}
+
+#[test]
+fn keyed_instances_unload_independently() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    let camera = engine.unload_module_with_key::<Camera>("p1").unwrap();
+    assert_eq!(camera.player, Some("p1"));
+    assert!(!engine.is_loaded_with_key::<Camera>("p1"));
+    assert!(engine.is_loaded_with_key::<Camera>("p2"));
+}
+
+#[test]
+fn send_to_reaches_the_unkeyed_instance_only() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    engine.dependency::<Camera>().unwrap();
+    engine.run_with(Bonus);
+    assert_eq!(camera(&mut engine, None).1, 5);
+    assert_eq!(camera(&mut engine, Some("p1")).1, 0);
+    assert_eq!(camera(&mut engine, Some("p2")).1, 0);
+}
+
+#[test]
+fn replies_reach_the_sender_only() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    engine.run_with_target(ModuleId::keyed::<Camera>("p2"), Focus);
+    assert_eq!(camera(&mut engine, Some("p1")).1, 0);
+    assert_eq!(camera(&mut engine, Some("p2")).1, 10);
+
+    // Without sender, the reply is dispatched to all of its listeners
+    engine.run_with(Query::new(Zoom));
+    assert_eq!(camera(&mut engine, Some("p1")).1, 10);
+    assert_eq!(camera(&mut engine, Some("p2")).1, 20);
+}
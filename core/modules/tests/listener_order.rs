@@ -0,0 +1,113 @@
+use std::cell::RefCell;
+
+use rgine_modules::{
+    events::{EventQueue, Listener, ListenerOrder},
+    AnyResult, Engine, Module, ModuleError,
+};
+
+thread_local! {
+    /// Names of the modules in the order they received the events
+    static DISPATCHED: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+fn dispatched() -> Vec<&'static str> {
+    DISPATCHED.with_borrow_mut(std::mem::take)
+}
+
+/// Declares a module named `$name` listening to `$event` with the order `$order`
+macro_rules! module {
+    ($name:ident, $event:ty, $order:expr) => {
+        struct $name;
+
+        impl Module for $name {
+            type ListeningTo = ($event,);
+
+            fn new(_: &mut Engine) -> AnyResult<Self> {
+                Ok(Self)
+            }
+        }
+
+        impl Listener<$event> for $name {
+            fn on_event(&mut self, _: &mut $event, _: &mut EventQueue) {
+                DISPATCHED.with_borrow_mut(|dispatched| dispatched.push(stringify!($name)));
+            }
+
+            fn order() -> ListenerOrder {
+                $order
+            }
+        }
+    };
+}
+
+struct Tick;
+module!(Low, Tick, ListenerOrder::new().priority(-1));
+module!(Default1, Tick, ListenerOrder::new());
+module!(High, Tick, ListenerOrder::new().priority(5));
+module!(Default2, Tick, ListenerOrder::new());
+
+/// `before` and `after` win over the priorities
+struct Step;
+module!(
+    Late,
+    Step,
+    ListenerOrder::new().priority(10).after::<Main>()
+);
+module!(Main, Step, ListenerOrder::new());
+module!(
+    Early,
+    Step,
+    ListenerOrder::new().priority(-10).before::<Main>()
+);
+
+struct Cycle;
+module!(Ping, Cycle, ListenerOrder::new().after::<Pong>());
+module!(Pong, Cycle, ListenerOrder::new().after::<Ping>());
+
+#[test]
+fn priority_then_load_order() {
+    let mut engine = Engine::new_without_logger::<Low>();
+    engine.dependency::<Default1>().unwrap();
+    engine.dependency::<High>().unwrap();
+    engine.dependency::<Default2>().unwrap();
+
+    engine.run_with(Tick);
+    assert_eq!(dispatched(), ["High", "Default1", "Default2", "Low"]);
+}
+
+#[test]
+fn before_and_after() {
+    let mut engine = Engine::new_without_logger::<Late>();
+    engine.dependency::<Main>().unwrap();
+    engine.dependency::<Early>().unwrap();
+
+    engine.run_with(Step);
+    assert_eq!(dispatched(), ["Early", "Main", "Late"]);
+}
+
+#[test]
+fn constraints_apply_whatever_the_load_order() {
+    let mut engine = Engine::new_without_logger::<Early>();
+    engine.dependency::<Main>().unwrap();
+    engine.dependency::<Late>().unwrap();
+
+    engine.run_with(Step);
+    assert_eq!(dispatched(), ["Early", "Main", "Late"]);
+}
+
+#[test]
+fn ordering_cycle() {
+    let mut engine = Engine::new_without_logger::<Ping>();
+    match engine.dependency::<Pong>() {
+        Err(ModuleError::OrderingCycle { event, modules }) => {
+            assert_eq!(event, std::any::type_name::<Cycle>());
+            assert_eq!(modules.len(), 2);
+        }
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("the cycle was not detected"),
+    }
+
+    // The module completing the cycle is not loaded
+    assert!(!engine.is_loaded::<Pong>());
+    engine.run_with(Cycle);
+    assert_eq!(dispatched(), ["Ping"]);
+}
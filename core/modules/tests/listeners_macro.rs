@@ -0,0 +1,55 @@
+use rgine_modules::{
+    events::{EventQueue, Implemented},
+    listeners, AnyResult, Engine, Module,
+};
+
+struct Add(u32);
+struct Double;
+struct Reset;
+
+#[derive(Default)]
+struct Counter {
+    value: u32,
+}
+
+impl Module for Counter {
+    type ListeningTo = Implemented;
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+}
+
+listeners! {
+    impl Listener<Add> for Counter {
+        fn on_event(&mut self, event: &mut Add, _: &mut EventQueue) {
+            self.value += event.0;
+        }
+    }
+
+    impl Listener<Double> for Counter {
+        fn on_event(&mut self, _: &mut Double, _: &mut EventQueue) {
+            self.value *= 2;
+        }
+    }
+
+    /// Last listener of the block
+    impl Listener<Reset> for Counter {
+        fn on_event(&mut self, _: &mut Reset, _: &mut EventQueue) {
+            self.value = 0;
+        }
+    }
+}
+
+#[test]
+fn every_implemented_listener_is_registered() {
+    let mut engine = Engine::new_without_logger::<Counter>();
+    let value = |engine: &mut Engine| engine.dependency::<Counter>().unwrap().read_state().value;
+
+    engine.run_with(Add(3));
+    engine.run_with(Double);
+    assert_eq!(value(&mut engine), 6);
+
+    engine.run_with(Reset);
+    assert_eq!(value(&mut engine), 0);
+}
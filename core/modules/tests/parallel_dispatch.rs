@@ -0,0 +1,71 @@
+#![cfg(feature = "parallel")]
+
+use std::{sync::Mutex, thread, time::Duration};
+
+use rgine_modules::{
+    events::Listeners,
+    parallel::{ParallelListener, ParallelQueue},
+    AnyResult, Engine, Module,
+};
+
+struct Tick;
+
+/// Modules in the order they handled `Tick`
+static HANDLED: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+struct Physics;
+
+impl Module for Physics {
+    type ListeningTo = ();
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self)
+    }
+
+    fn listeners(listeners: &mut Listeners<Self>) {
+        listeners.listen_parallel::<Tick>();
+    }
+}
+
+impl ParallelListener<Tick> for Physics {
+    fn on_event(&mut self, _: &Tick, _: &mut ParallelQueue) {
+        // Long enough for a concurrently dispatched listener to finish first
+        thread::sleep(Duration::from_millis(50));
+        HANDLED.lock().unwrap().push("physics");
+    }
+}
+
+/// Depends on `Physics`, so it must handle `Tick` after it
+struct Render;
+
+impl Module for Render {
+    type ListeningTo = ();
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.dependency::<Physics>()?;
+        Ok(Self)
+    }
+
+    fn listeners(listeners: &mut Listeners<Self>) {
+        listeners.listen_parallel::<Tick>();
+    }
+}
+
+impl ParallelListener<Tick> for Render {
+    fn on_event(&mut self, _: &Tick, _: &mut ParallelQueue) {
+        HANDLED.lock().unwrap().push("render");
+    }
+}
+
+#[test]
+fn dependent_modules_are_not_dispatched_concurrently() {
+    // Enough threads for the listeners to run concurrently if they were batched together
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build_global()
+        .unwrap();
+
+    let mut engine = Engine::new_without_logger::<Render>();
+    engine.run_with(Tick);
+    assert_eq!(*HANDLED.lock().unwrap(), ["physics", "render"]);
+}
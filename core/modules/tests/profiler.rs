@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use rgine_modules::{
+    events::{EventQueue, Listener},
+    profiler::Profiler,
+    AnyResult, Engine, Module, ModuleId,
+};
+
+struct Tick;
+
+struct Physics;
+
+impl Module for Physics {
+    type ListeningTo = (Tick,);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self)
+    }
+}
+
+impl Listener<Tick> for Physics {
+    fn on_event(&mut self, _: &mut Tick, _: &mut EventQueue) {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+struct Game;
+
+impl Module for Game {
+    type ListeningTo = (Tick,);
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.dependency::<Physics>()?;
+        Ok(Self)
+    }
+}
+
+impl Listener<Tick> for Game {
+    fn on_event(&mut self, _: &mut Tick, _: &mut EventQueue) {}
+}
+
+#[test]
+fn frames_summarize_their_listeners() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    engine.start_profiling(Profiler::new().slowest_listeners(1));
+    engine.run_with(Tick);
+    engine.run_with(Tick);
+    engine.flush_deferred_events();
+    engine.run_with(Tick);
+    engine.flush_deferred_events();
+
+    let profiler = engine.profiler().unwrap();
+    let frames = profiler.frames().collect::<Vec<_>>();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].index, 0);
+    assert_eq!(frames[0].listeners, 4);
+    assert!(frames[0].listeners_time >= Duration::from_millis(40));
+    assert_eq!(frames[1].index, 1);
+    assert_eq!(frames[1].listeners, 2);
+
+    let slowest = &frames[1].slowest;
+    assert_eq!(slowest.len(), 1);
+    assert_eq!(slowest[0].module, ModuleId::of::<Physics>());
+    assert_eq!(slowest[0].event, "profiler::Tick");
+    assert_eq!(
+        profiler.slowest_frame().unwrap().index,
+        0,
+        "two ticks were dispatched during the first frame"
+    );
+
+    // Only the summaries are kept by default
+    assert_eq!(profiler.chrome_trace().lines().count(), 3);
+}
+
+#[test]
+fn chrome_trace_lists_the_spans() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    engine.start_profiling(Profiler::new().trace(true));
+    engine.run_with(Tick);
+    engine.flush_deferred_events();
+
+    let trace = engine.stop_profiling().unwrap().chrome_trace();
+    let spans = trace
+        .lines()
+        // Timestamps vary between runs
+        .map(|line| line.split(r#","ts":"#).next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        spans,
+        [
+            r#"{"displayTimeUnit":"ms","traceEvents":["#,
+            r#"{"name":"thread_name","ph":"M","pid":1,"tid":0,"args":{"name":"main"}},"#,
+            r#"{"name":"profiler::Physics","cat":"listener","ph":"X""#,
+            r#"{"name":"profiler::Game","cat":"listener","ph":"X""#,
+            r#"{"name":"profiler::Tick","cat":"event","ph":"X""#,
+            r#"{"name":"Frame 1","cat":"frame","ph":"i","s":"g""#,
+            "]}",
+        ]
+    );
+    assert!(engine.profiler().is_none());
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "replay")]
+
+use rgine_modules::{
+    events::{EventQueue, Listener},
+    replay::{EventRecorder, RecordableEvent, Recording, ReplayError, Replayer},
+    snapshot::SnapshotModule,
+    AnyResult, Engine, Module,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Add(u32);
+
+impl RecordableEvent for Add {
+    const NAME: &'static str = "add";
+}
+
+/// Recorded under the same name as `Add`
+#[derive(Serialize, Deserialize)]
+struct Sub(u32);
+
+impl RecordableEvent for Sub {
+    const NAME: &'static str = "add";
+}
+
+/// Sums the added values, and doubles the sum on the next update
+#[derive(Default)]
+struct Sum {
+    value: u32,
+}
+
+struct Double;
+
+impl Module for Sum {
+    type ListeningTo = (Add, Double);
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.enable_snapshot::<Self>();
+        Ok(Self::default())
+    }
+}
+
+impl Listener<Add> for Sum {
+    fn on_event(&mut self, event: &mut Add, queue: &mut EventQueue) {
+        self.value += event.0;
+        queue.push_next_update(Double);
+    }
+}
+
+impl Listener<Double> for Sum {
+    fn on_event(&mut self, _: &mut Double, _: &mut EventQueue) {
+        self.value *= 2;
+    }
+}
+
+impl SnapshotModule for Sum {
+    type Snapshot = u32;
+    const NAME: &'static str = "sum";
+
+    fn snapshot(&self) -> u32 {
+        self.value
+    }
+
+    fn restore(&mut self, value: u32) {
+        self.value = value;
+    }
+}
+
+fn play(engine: &mut Engine) {
+    engine.run_with(Add(1));
+    engine.flush_deferred_events();
+    engine.run_with(Add(2));
+    engine.run_with(Add(3));
+    engine.flush_deferred_events();
+}
+
+fn record() -> (Recording, Option<u64>) {
+    let mut engine = Engine::new_without_logger::<Sum>();
+    engine
+        .start_recording(EventRecorder::new().record::<Add>().hash_state(true))
+        .unwrap();
+    play(&mut engine);
+    (engine.stop_recording().unwrap(), engine.state_hash())
+}
+
+#[test]
+fn replays_a_saved_recording_to_the_same_state() {
+    let (recording, state_hash) = record();
+    assert_eq!(recording.events().collect::<Vec<_>>(), ["add"; 3]);
+
+    let path = std::env::temp_dir().join(format!("rgine_replay_{}.json", std::process::id()));
+    recording.save(&path).unwrap();
+    let loaded = Recording::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    let loaded = loaded.unwrap();
+    assert_eq!(loaded, recording);
+
+    let mut engine = Engine::new_without_logger::<Sum>();
+    Replayer::new()
+        .event::<Add>()
+        .run(&mut engine, &loaded)
+        .unwrap();
+    assert_eq!(engine.state_hash(), state_hash);
+    assert_eq!(engine.dependency::<Sum>().unwrap().read_state().value, 28);
+}
+
+#[test]
+fn detects_diverging_states() {
+    let (recording, _) = record();
+
+    let mut engine = Engine::new_without_logger::<Sum>();
+    engine.dependency_mut::<Sum>().unwrap().write_state().value = 7;
+    let result = Replayer::new().event::<Add>().run(&mut engine, &recording);
+    assert!(matches!(
+        result,
+        Err(ReplayError::Diverged { frame: 0, .. })
+    ));
+}
+
+#[test]
+fn rejects_duplicate_names() {
+    let mut engine = Engine::new_without_logger::<Sum>();
+    let result = engine.start_recording(EventRecorder::new().record::<Add>().record::<Sub>());
+    assert!(matches!(result, Err(ReplayError::DuplicateName("add"))));
+    assert!(engine.stop_recording().is_none());
+
+    let (recording, _) = record();
+    let result = Replayer::new()
+        .event::<Add>()
+        .event::<Sub>()
+        .run(&mut engine, &recording);
+    assert!(matches!(result, Err(ReplayError::DuplicateName("add"))));
+}
@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use rgine_modules::{
+    events::{EventQueue, Listener, ScheduleBudget},
+    AnyResult, Engine, Module, ScheduleError,
+};
+
+/// Pushes itself forever, queuing a command every time
+struct Loop;
+/// Starts the loop
+struct Kick;
+
+#[derive(Default)]
+struct Looper {
+    dispatched: usize,
+}
+
+impl Module for Looper {
+    type ListeningTo = (Kick, Loop);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+}
+
+impl Listener<Loop> for Looper {
+    fn on_event(&mut self, _: &mut Loop, queue: &mut EventQueue) {
+        self.dispatched += 1;
+        queue.push(Loop);
+        queue.push_command(|engine| {
+            engine
+                .dependency_mut::<Commands>()
+                .unwrap()
+                .write_state()
+                .run += 1;
+        });
+    }
+}
+
+impl Listener<Kick> for Looper {
+    fn on_event(&mut self, _: &mut Kick, queue: &mut EventQueue) {
+        queue.push(Loop);
+    }
+}
+
+#[derive(Default)]
+struct Commands {
+    run: usize,
+}
+
+impl Module for Commands {
+    type ListeningTo = ();
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+}
+
+fn engine(budget: ScheduleBudget) -> Engine {
+    let mut engine = Engine::new_without_logger::<Looper>();
+    engine.dependency::<Commands>().unwrap();
+    engine.set_schedule_budget(budget);
+    engine
+}
+
+#[test]
+fn commands_run_when_too_many_dispatches() {
+    let mut engine = engine(ScheduleBudget::unlimited().max_dispatches(5));
+    let result = engine.try_run_with(Loop);
+    assert!(matches!(
+        result,
+        Err(ScheduleError::TooManyDispatches { limit: 5, .. })
+    ));
+    assert_eq!(
+        engine
+            .dependency::<Looper>()
+            .unwrap()
+            .read_state()
+            .dispatched,
+        5
+    );
+    assert_eq!(engine.dependency::<Commands>().unwrap().read_state().run, 5);
+}
+
+#[test]
+fn commands_run_when_too_deep() {
+    let mut engine = engine(ScheduleBudget::unlimited().max_depth(3));
+    let result = engine.try_run_with(Loop);
+    assert!(matches!(
+        result,
+        Err(ScheduleError::TooDeep { limit: 3, .. })
+    ));
+    // The root event and its 3 descendants
+    assert_eq!(engine.dependency::<Commands>().unwrap().read_state().run, 4);
+}
+
+#[test]
+fn errors_report_the_chain_of_events() {
+    let mut engine = engine(ScheduleBudget::unlimited().max_depth(3));
+    let Err(error) = engine.try_run_with(Kick) else {
+        panic!("the schedule should exceed its budget");
+    };
+    let ScheduleError::TooDeep { chain, .. } = &error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(
+        chain,
+        &[
+            "Kick (inside of schedule_budget)",
+            "Loop (inside of schedule_budget) x4"
+        ]
+    );
+    assert!(error
+        .to_string()
+        .ends_with("Kick (inside of schedule_budget) -> Loop (inside of schedule_budget) x4"));
+}
+
+#[test]
+fn out_of_time_events_are_deferred_to_the_next_update() {
+    let mut engine = engine(ScheduleBudget::unlimited().time_budget(Duration::ZERO));
+    let dispatched = |engine: &mut Engine| {
+        engine
+            .dependency::<Looper>()
+            .unwrap()
+            .read_state()
+            .dispatched
+    };
+
+    // The root event is always dispatched, the Loop it pushed waits for the next update
+    engine.try_run_with(Loop).unwrap();
+    assert_eq!(dispatched(&mut engine), 1);
+    engine.try_run_with(Kick).unwrap();
+    assert_eq!(dispatched(&mut engine), 1);
+
+    engine.flush_deferred_events();
+    assert_eq!(dispatched(&mut engine), 3);
+    assert_eq!(engine.dependency::<Commands>().unwrap().read_state().run, 3);
+}
@@ -0,0 +1,233 @@
+#![cfg(feature = "snapshot")]
+
+use rgine_modules::{
+    snapshot::{Snapshot, SnapshotError, SnapshotModule},
+    AnyResult, Engine, Module,
+};
+
+#[derive(Default)]
+struct Score {
+    points: u32,
+}
+
+impl Module for Score {
+    type ListeningTo = ();
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.enable_snapshot::<Self>();
+        Ok(Self::default())
+    }
+}
+
+impl SnapshotModule for Score {
+    type Snapshot = u32;
+    const NAME: &'static str = "score";
+    const VERSION: u32 = 1;
+
+    fn snapshot(&self) -> u32 {
+        self.points
+    }
+
+    fn restore(&mut self, points: u32) {
+        self.points = points;
+    }
+}
+
+/// Loaded after `Score`
+#[derive(Default)]
+struct Player {
+    name: String,
+}
+
+impl Module for Player {
+    type ListeningTo = ();
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.dependency::<Score>()?;
+        engine.enable_snapshot::<Self>();
+        Ok(Self::default())
+    }
+}
+
+impl SnapshotModule for Player {
+    type Snapshot = String;
+    const NAME: &'static str = "player";
+
+    fn snapshot(&self) -> String {
+        self.name.clone()
+    }
+
+    fn restore(&mut self, name: String) {
+        self.name = name;
+    }
+}
+
+fn set(engine: &mut Engine, points: u32, name: &str) {
+    engine
+        .dependency_mut::<Score>()
+        .unwrap()
+        .write_state()
+        .points = points;
+    engine
+        .dependency_mut::<Player>()
+        .unwrap()
+        .write_state()
+        .name = name.to_owned();
+}
+
+fn get(engine: &mut Engine) -> (u32, String) {
+    let points = engine.dependency::<Score>().unwrap().read_state().points;
+    let name = engine
+        .dependency::<Player>()
+        .unwrap()
+        .read_state()
+        .name
+        .clone();
+    (points, name)
+}
+
+/// Rewrites the serialized `snapshot`, to simulate one made by another build
+fn edit(snapshot: &Snapshot, from: &str, to: &str) -> Snapshot {
+    let json = String::from_utf8(snapshot.to_bytes()).unwrap();
+    assert!(json.contains(from), "{from} not found in {json}");
+    Snapshot::from_bytes(json.replace(from, to).as_bytes()).unwrap()
+}
+
+#[test]
+fn restores_the_snapshotted_state() {
+    let mut engine = Engine::new_without_logger::<Player>();
+    set(&mut engine, 12, "ada");
+    let snapshot = engine.snapshot().unwrap();
+    assert_eq!(snapshot.modules().collect::<Vec<_>>(), ["player", "score"]);
+
+    set(&mut engine, 0, "bob");
+    let snapshot = Snapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+    engine.restore(&snapshot).unwrap();
+    assert_eq!(get(&mut engine), (12, "ada".to_owned()));
+}
+
+#[test]
+fn rejects_other_module_versions() {
+    let mut engine = Engine::new_without_logger::<Player>();
+    let snapshot = edit(
+        &engine.snapshot().unwrap(),
+        r#""version":1"#,
+        r#""version":0"#,
+    );
+    assert!(matches!(
+        engine.restore(&snapshot),
+        Err(SnapshotError::VersionMismatch {
+            expected: 1,
+            found: 0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn rejects_snapshots_missing_a_module() {
+    let mut engine = Engine::new_without_logger::<Player>();
+    let snapshot = edit(&engine.snapshot().unwrap(), r#""player""#, r#""enemy""#);
+    assert!(matches!(
+        engine.restore(&snapshot),
+        Err(SnapshotError::Missing(name)) if name == "player"
+    ));
+}
+
+#[test]
+fn leaves_the_engine_untouched_on_error() {
+    let mut engine = Engine::new_without_logger::<Player>();
+    set(&mut engine, 12, "ada");
+    // `Score` is decoded fine, but `Player` is missing
+    let snapshot = edit(&engine.snapshot().unwrap(), r#""player""#, r#""enemy""#);
+
+    set(&mut engine, 0, "bob");
+    assert!(engine.restore(&snapshot).is_err());
+    assert_eq!(get(&mut engine), (0, "bob".to_owned()));
+}
+
+/// Snapshotted under the same name as `Score`
+struct Rival;
+
+impl Module for Rival {
+    type ListeningTo = ();
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.dependency::<Score>()?;
+        engine.enable_snapshot::<Self>();
+        Ok(Self)
+    }
+}
+
+impl SnapshotModule for Rival {
+    type Snapshot = ();
+    const NAME: &'static str = "score";
+
+    fn snapshot(&self) {}
+
+    fn restore(&mut self, _: ()) {}
+}
+
+#[test]
+fn rejects_duplicate_names() {
+    let mut engine = Engine::new_without_logger::<Rival>();
+    assert!(matches!(
+        engine.snapshot(),
+        Err(SnapshotError::DuplicateName(name)) if name == "score"
+    ));
+
+    let snapshot = Engine::new_without_logger::<Player>().snapshot().unwrap();
+    assert!(matches!(
+        engine.restore(&snapshot),
+        Err(SnapshotError::DuplicateName(name)) if name == "score"
+    ));
+}
+
+/// Snapshots a keyed instance of `Score` for each player
+struct Scores;
+
+impl Module for Scores {
+    type ListeningTo = ();
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        engine.dependency_with_key::<Score>("p1")?;
+        engine.dependency_with_key::<Score>("p2")?;
+        Ok(Self)
+    }
+}
+
+#[test]
+fn keyed_instances_are_snapshotted_separately() {
+    let mut engine = Engine::new_without_logger::<Scores>();
+    let points = |engine: &mut Engine, key| {
+        engine
+            .dependency_with_key::<Score>(key)
+            .unwrap()
+            .read_state()
+            .points
+    };
+    engine
+        .dependency_mut_with_key::<Score>("p1")
+        .unwrap()
+        .write_state()
+        .points = 3;
+    let snapshot = engine.snapshot().unwrap();
+    assert_eq!(
+        snapshot.modules().collect::<Vec<_>>(),
+        ["score[p1]", "score[p2]"]
+    );
+
+    engine
+        .dependency_mut_with_key::<Score>("p1")
+        .unwrap()
+        .write_state()
+        .points = 0;
+    engine
+        .dependency_mut_with_key::<Score>("p2")
+        .unwrap()
+        .write_state()
+        .points = 5;
+    engine.restore(&snapshot).unwrap();
+    assert_eq!(points(&mut engine, "p1"), 3);
+    assert_eq!(points(&mut engine, "p2"), 0);
+}
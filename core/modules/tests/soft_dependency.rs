@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rgine_modules::{AnyResult, Engine, Module, ModuleId, SoftDependency};
+
+#[derive(Default)]
+struct Audio {
+    volume: u32,
+}
+
+impl Module for Audio {
+    type ListeningTo = ();
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+}
+
+/// Fails to initialize until connected
+struct Gamepad;
+
+static CONNECTED: AtomicBool = AtomicBool::new(false);
+
+impl Module for Gamepad {
+    type ListeningTo = ();
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        match CONNECTED.load(Ordering::Relaxed) {
+            true => Ok(Self),
+            false => Err("no gamepad connected".into()),
+        }
+    }
+}
+
+struct Game {
+    has_audio: bool,
+    has_gamepad: bool,
+}
+
+impl Module for Game {
+    type ListeningTo = ();
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        Ok(Self {
+            has_audio: engine.optional_dependency::<Audio>().is_some(),
+            has_gamepad: engine.optional_dependency::<Gamepad>().is_some(),
+        })
+    }
+}
+
+#[test]
+fn optional_dependencies_are_none_when_they_fail_to_load() {
+    let mut engine = Engine::new_without_logger::<Game>();
+    let game = engine.dependency::<Game>().unwrap();
+    let game = game.read_state();
+    assert!(game.has_audio);
+    assert!(!game.has_gamepad);
+
+    assert!(!engine.is_loaded::<Gamepad>());
+
+    // Loaded later on, it is not a dependency of `Game`
+    CONNECTED.store(true, Ordering::Relaxed);
+    engine.dependency::<Gamepad>().unwrap();
+    let modules = engine.modules();
+    let game = modules.iter().find(|m| m.id == ModuleId::of::<Game>());
+    assert_eq!(game.unwrap().dependencies, [ModuleId::of::<Audio>()]);
+}
+
+struct Hud {
+    audio: SoftDependency<Audio>,
+}
+
+impl Module for Hud {
+    type ListeningTo = ();
+
+    fn new(engine: &mut Engine) -> AnyResult<Self> {
+        Ok(Self {
+            audio: engine.soft_dependency::<Audio>(),
+        })
+    }
+}
+
+fn hud_volume(engine: &mut Engine) -> Option<u32> {
+    let hud = engine.dependency::<Hud>().unwrap();
+    let hud = hud.read_state();
+    let audio = hud.audio.get()?;
+    let volume = audio.read_state().volume;
+    Some(volume)
+}
+
+#[test]
+fn soft_dependencies_follow_their_module() {
+    let mut engine = Engine::new_without_logger::<Hud>();
+    assert!(!engine.is_loaded::<Audio>());
+    assert_eq!(hud_volume(&mut engine), None);
+
+    engine
+        .dependency_mut::<Audio>()
+        .unwrap()
+        .write_state()
+        .volume = 7;
+    assert_eq!(hud_volume(&mut engine), Some(7));
+
+    // Soft dependencies don't keep the module loaded
+    engine.unload_module::<Audio>().unwrap();
+    assert_eq!(hud_volume(&mut engine), None);
+
+    engine.dependency::<Audio>().unwrap();
+    assert_eq!(hud_volume(&mut engine), Some(0));
+}
@@ -0,0 +1,69 @@
+use std::{
+    sync::mpsc::{channel, Receiver},
+    time::{Duration, Instant},
+};
+
+use rgine_modules::{
+    events::{EventQueue, Listener},
+    tasks::unblock,
+    AnyResult, Engine, Module,
+};
+
+/// Spawns a task reading the save from the receiver
+struct Load(Receiver<u32>);
+/// Output of the task
+struct Loaded(u32);
+
+#[derive(Default)]
+struct Save {
+    level: Option<u32>,
+}
+
+impl Module for Save {
+    type ListeningTo = (Load, Loaded);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+}
+
+impl Listener<Load> for Save {
+    fn on_event(&mut self, event: &mut Load, queue: &mut EventQueue) {
+        let (_, receiver) = channel();
+        let receiver = std::mem::replace(&mut event.0, receiver);
+        queue.spawn(async move { Loaded(unblock(move || receiver.recv().unwrap()).await) });
+    }
+}
+
+impl Listener<Loaded> for Save {
+    fn on_event(&mut self, event: &mut Loaded, _: &mut EventQueue) {
+        self.level = Some(event.0);
+    }
+}
+
+fn level(engine: &mut Engine) -> Option<u32> {
+    engine.dependency::<Save>().unwrap().read_state().level
+}
+
+#[test]
+fn task_outputs_are_dispatched_once_completed() {
+    let mut engine = Engine::new_without_logger::<Save>();
+    let (sender, receiver) = channel();
+    engine.run_with(Load(receiver));
+    assert_eq!(engine.executor().len(), 1);
+
+    // Blocked on the other thread, the task is still pending
+    engine.flush_deferred_events();
+    engine.flush_deferred_events();
+    assert_eq!(engine.executor().len(), 1);
+    assert_eq!(level(&mut engine), None);
+
+    sender.send(3).unwrap();
+    let started_at = Instant::now();
+    while !engine.executor().is_empty() {
+        assert!(started_at.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(1));
+        engine.flush_deferred_events();
+    }
+    assert_eq!(level(&mut engine), Some(3));
+}
@@ -0,0 +1,133 @@
+use std::cell::RefCell;
+
+use rgine_modules::{
+    events::{EventQueue, Listener},
+    AnyResult, Dependency, Engine, Module, ModuleError,
+};
+
+/// Listened to by both `Alpha` and `Beta`
+struct Shared;
+/// Makes `Beta` unload `Alpha` with a command
+struct UnloadAlpha;
+
+thread_local! {
+    /// Lifecycle hooks called on `Alpha`
+    static HOOKS: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Default)]
+struct Alpha {
+    received: usize,
+}
+
+#[derive(Default)]
+struct Beta {
+    received: usize,
+}
+
+/// Keeps `Beta` in use
+struct User {
+    _beta: Dependency<Beta>,
+}
+
+impl Module for Alpha {
+    type ListeningTo = (Shared,);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+
+    fn on_load(&mut self) {
+        HOOKS.with_borrow_mut(|hooks| hooks.push("load"));
+    }
+
+    fn on_unload(&mut self) {
+        HOOKS.with_borrow_mut(|hooks| hooks.push("unload"));
+    }
+}
+
+impl Module for Beta {
+    type ListeningTo = (Shared, UnloadAlpha);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self::default())
+    }
+}
+
+impl Module for User {
+    type ListeningTo = ();
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        Ok(Self {
+            _beta: ctx.dependency()?,
+        })
+    }
+}
+
+impl Listener<Shared> for Alpha {
+    fn on_event(&mut self, _: &mut Shared, _: &mut EventQueue) {
+        self.received += 1;
+    }
+}
+
+impl Listener<Shared> for Beta {
+    fn on_event(&mut self, _: &mut Shared, _: &mut EventQueue) {
+        self.received += 1;
+    }
+}
+
+impl Listener<UnloadAlpha> for Beta {
+    fn on_event(&mut self, _: &mut UnloadAlpha, queue: &mut EventQueue) {
+        queue.push_command(|engine| {
+            engine.unload_module::<Alpha>().unwrap();
+        });
+    }
+}
+
+fn received_by_beta(engine: &mut Engine) -> usize {
+    engine.dependency::<Beta>().unwrap().read_state().received
+}
+
+#[test]
+fn other_subscribers_keep_receiving() {
+    let mut engine = Engine::new_without_logger::<Alpha>();
+    engine.dependency::<Beta>().unwrap();
+    engine.run_with(Shared);
+
+    let alpha = engine.unload_module::<Alpha>().unwrap();
+    assert_eq!(alpha.received, 1);
+    assert!(!engine.is_loaded::<Alpha>());
+
+    engine.run_with(Shared);
+    assert_eq!(received_by_beta(&mut engine), 2);
+}
+
+#[test]
+fn unloading_a_module_in_use_keeps_it_loaded() {
+    let mut engine = Engine::new_without_logger::<User>();
+
+    let result = engine.unload_module::<Beta>();
+    assert!(matches!(result, Err(ModuleError::InUse)));
+    assert!(engine.is_loaded::<Beta>());
+
+    engine.run_with(Shared);
+    assert_eq!(received_by_beta(&mut engine), 1);
+}
+
+#[test]
+fn unloading_a_missing_module_fails() {
+    let mut engine = Engine::new_without_logger::<Beta>();
+    let result = engine.unload_module::<Alpha>();
+    assert!(matches!(result, Err(ModuleError::NotFound)));
+}
+
+#[test]
+fn listeners_unload_modules_with_commands() {
+    let mut engine = Engine::new_without_logger::<Alpha>();
+    engine.dependency::<Beta>().unwrap();
+    assert_eq!(HOOKS.with_borrow(Vec::clone), ["load"]);
+
+    engine.run_with(UnloadAlpha);
+    assert!(!engine.is_loaded::<Alpha>());
+    assert_eq!(HOOKS.with_borrow(Vec::clone), ["load", "unload"]);
+}
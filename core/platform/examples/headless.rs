@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use rgine_logger::info;
+use rgine_modules::prelude::*;
+use rgine_platform::headless::{
+    module::RequestExitEvent, HeadlessPlatformConfig, HeadlessPlatformEngineExt,
+};
+
+fn main() {
+    Engine::new::<ExampleModule>().run_headless(HeadlessPlatformConfig {
+        fixed_delta: Some(Duration::from_secs(1) / 60),
+        ..Default::default()
+    });
+}
+
+struct ExampleModule;
+impl Module for ExampleModule {
+    type ListeningTo = (StartEvent, TickedUpdate, ShutdownEvent);
+    fn new(ctx: &mut Engine) -> rgine_modules::AnyResult<Self> {
+        ctx.dependency::<ClockModule>()?;
+        Ok(ExampleModule)
+    }
+}
+
+impl Listener<StartEvent> for ExampleModule {
+    fn on_event(&mut self, _: &mut StartEvent, _: &mut EventQueue) {
+        info!("On start!")
+    }
+}
+impl Listener<TickedUpdate> for ExampleModule {
+    fn on_event(&mut self, event: &mut TickedUpdate, queue: &mut EventQueue) {
+        info!("On tick {}!", event.tick);
+        if event.tick == 9 {
+            queue.push(RequestExitEvent);
+        }
+    }
+}
+impl Listener<ShutdownEvent> for ExampleModule {
+    fn on_event(&mut self, _: &mut ShutdownEvent, _: &mut EventQueue) {
+        info!("On shutdown!")
+    }
+}
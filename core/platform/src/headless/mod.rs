@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+use self::module::HeadlessPlatformModule;
+use rgine_modules::{
+    standards::{ClockEvent, PlatformUpdateEvent, ShutdownEvent, StartEvent},
+    Engine,
+};
+
+pub mod module;
+
+pub trait HeadlessPlatformEngineExt {
+    /// Runs the engine without any window nor display.
+    ///
+    /// The engine is given back once stopped so that the state of its modules can be inspected.
+    fn run_headless(self, config: HeadlessPlatformConfig) -> Engine;
+}
+
+impl HeadlessPlatformEngineExt for Engine {
+    fn run_headless(mut self, config: HeadlessPlatformConfig) -> Engine {
+        let platform = self.dependency::<HeadlessPlatformModule>().expect(
+            "Failed to load headless platform module from platform layer on headless platform.",
+        );
+        if let Some(delta) = config.fixed_delta {
+            self.run_with(ClockEvent::SetFixedDelta(Some(delta)));
+        }
+        self.run_with(StartEvent);
+
+        let mut iteration = 0;
+        while config.iterations.is_none_or(|n| iteration < n) && !platform.read_state().should_close
+        {
+            let start = Instant::now();
+            self.flush_deferred_events();
+            self.run_with(PlatformUpdateEvent);
+            iteration += 1;
+
+            if let Some(remaining) = config
+                .update_interval
+                .and_then(|interval| interval.checked_sub(start.elapsed()))
+            {
+                std::thread::sleep(remaining);
+            }
+        }
+
+        self.run_with(ShutdownEvent);
+        drop(platform);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HeadlessPlatformConfig {
+    /// Number of platform iterations to run, runs until a module sends a [`RequestExitEvent`](module::RequestExitEvent) if `None`.
+    pub iterations: Option<u64>,
+    /// Time reported to the [`ClockModule`](rgine_modules::standards::ClockModule) as elapsed between two iterations,
+    /// measured from the system clock if `None`.
+    ///
+    /// Combined with `iterations`, this makes a run fully deterministic.
+    pub fixed_delta: Option<Duration>,
+    /// Minimum duration of an iteration, the thread sleeps for the time left if any.
+    pub update_interval: Option<Duration>,
+}
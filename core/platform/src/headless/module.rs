@@ -0,0 +1,24 @@
+use rgine_modules::{
+    events::{EventQueue, Listener},
+    Engine, Module,
+};
+
+/// Stops the headless platform at the end of the current iteration.
+pub struct RequestExitEvent;
+
+pub struct HeadlessPlatformModule {
+    pub should_close: bool,
+}
+impl Module for HeadlessPlatformModule {
+    type ListeningTo = (RequestExitEvent,);
+    fn new(_: &mut Engine) -> rgine_modules::AnyResult<Self> {
+        Ok(Self {
+            should_close: false,
+        })
+    }
+}
+impl Listener<RequestExitEvent> for HeadlessPlatformModule {
+    fn on_event(&mut self, _: &mut RequestExitEvent, _: &mut EventQueue) {
+        self.should_close = true;
+    }
+}
@@ -1,2 +1,4 @@
+#[cfg(feature = "headless")]
+pub mod headless;
 #[cfg(feature = "window")]
 pub mod window;
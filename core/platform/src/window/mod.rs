@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use self::module::WindowPlatformModule;
 use rgine_modules::{
-    standards::{ShutdownEvent, StartEvent},
+    standards::{PlatformUpdateEvent, ShutdownEvent, StartEvent},
     Engine,
 };
 use winit::{
@@ -43,7 +43,59 @@ pub struct WindowPlatformConfig {
 impl Default for WindowPlatformConfig {
     fn default() -> Self {
         Self {
-            window_attributes: WindowAttributes::default().with_title("Rgine window"),
+            window_attributes: WindowAttributes::default().with_title(DEFAULT_TITLE),
+        }
+    }
+}
+
+const DEFAULT_TITLE: &str = "Rgine window";
+
+#[cfg(feature = "config")]
+impl WindowPlatformConfig {
+    /// Builds the window from the `[window]` section of the engine configuration, see [`WindowConfig`].
+    pub fn from_engine_config(engine: &Engine) -> Result<Self, rgine_modules::config::ConfigError> {
+        let config = engine.config::<WindowConfig>()?;
+        let mut window_attributes = WindowAttributes::default()
+            .with_title(config.title)
+            .with_resizable(config.resizable);
+        if let Some([width, height]) = config.size {
+            window_attributes =
+                window_attributes.with_inner_size(winit::dpi::LogicalSize::new(width, height));
+        }
+        Ok(Self { window_attributes })
+    }
+}
+
+/// Section `[window]` of the engine configuration
+#[cfg(feature = "config")]
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub title: String,
+    /// Logical size of the window, chosen by the platform if `None`
+    pub size: Option<[u32; 2]>,
+    pub resizable: bool,
+}
+
+#[cfg(feature = "config")]
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: DEFAULT_TITLE.to_owned(),
+            size: None,
+            resizable: true,
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl rgine_modules::config::ModuleConfig for WindowConfig {
+    const SECTION: &'static str = "window";
+
+    fn validate(&self) -> Result<(), String> {
+        match self.size {
+            Some([0, _] | [_, 0]) => Err("the window size can't be 0".to_owned()),
+            _ => Ok(()),
         }
     }
 }
@@ -60,7 +112,7 @@ impl<'a> EngineWindowPlatformWrapper<'a> {
 }
 
 pub struct WindowReadyEvent;
-pub struct OnWindowPlatformUpdate;
+pub use rgine_modules::standards::PlatformUpdateEvent as OnWindowPlatformUpdate;
 
 impl<'a> ApplicationHandler for EngineWindowPlatformWrapper<'a> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
@@ -93,7 +145,8 @@ impl<'a> ApplicationHandler for EngineWindowPlatformWrapper<'a> {
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        self.engine.run_with(OnWindowPlatformUpdate);
+        self.engine.flush_deferred_events();
+        self.engine.run_with(PlatformUpdateEvent);
         if self
             .engine
             .dependency::<WindowPlatformModule>()
@@ -0,0 +1,81 @@
+#![cfg(feature = "headless")]
+
+use std::{cell::Cell, time::Duration};
+
+use rgine_modules::{prelude::*, standards::ClockModule};
+use rgine_platform::headless::{
+    module::RequestExitEvent, HeadlessPlatformConfig, HeadlessPlatformEngineExt,
+};
+
+thread_local! {
+    /// Tick on which the game requests the exit, if any
+    static EXIT_AT: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+#[derive(Default)]
+struct Game {
+    ticks: Vec<u64>,
+    started: bool,
+    shut_down: bool,
+}
+
+impl Module for Game {
+    type ListeningTo = (StartEvent, TickedUpdate, ShutdownEvent);
+
+    fn new(ctx: &mut Engine) -> AnyResult<Self> {
+        ctx.dependency::<ClockModule>()?;
+        Ok(Self::default())
+    }
+}
+
+impl Listener<StartEvent> for Game {
+    fn on_event(&mut self, _: &mut StartEvent, _: &mut EventQueue) {
+        self.started = true;
+    }
+}
+
+impl Listener<TickedUpdate> for Game {
+    fn on_event(&mut self, event: &mut TickedUpdate, queue: &mut EventQueue) {
+        self.ticks.push(event.tick);
+        if EXIT_AT.get() == Some(event.tick) {
+            queue.push(RequestExitEvent);
+        }
+    }
+}
+
+impl Listener<ShutdownEvent> for Game {
+    fn on_event(&mut self, _: &mut ShutdownEvent, _: &mut EventQueue) {
+        self.shut_down = true;
+    }
+}
+
+/// One tick per iteration
+const FIXED_DELTA: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Returns the ticks received, and whether the game was started and shut down
+fn run(exit_at: Option<u64>, iterations: Option<u64>) -> (Vec<u64>, bool, bool) {
+    EXIT_AT.set(exit_at);
+    let mut engine = Engine::new_without_logger::<Game>().run_headless(HeadlessPlatformConfig {
+        iterations,
+        fixed_delta: Some(FIXED_DELTA),
+        ..Default::default()
+    });
+    let game = engine.dependency::<Game>().unwrap();
+    let game = game.read_state();
+    (game.ticks.clone(), game.started, game.shut_down)
+}
+
+#[test]
+fn stops_after_the_given_iterations() {
+    let (ticks, started, shut_down) = run(None, Some(5));
+    assert!(started);
+    assert_eq!(ticks, [0, 1, 2, 3, 4]);
+    assert!(shut_down);
+}
+
+#[test]
+fn stops_when_a_module_requests_the_exit() {
+    let (ticks, _, shut_down) = run(Some(2), None);
+    assert_eq!(ticks, [0, 1, 2]);
+    assert!(shut_down);
+}
@@ -1,4 +1,4 @@
-use std::{f32::consts::PI, time::Instant};
+use std::{f32::consts::PI, time::Duration};
 
 use rgine::prelude::*;
 
@@ -7,16 +7,17 @@ fn main() {
 }
 
 struct Example {
-    time: Instant,
+    time: Duration,
     characters_sheet: Option<SpriteSheetHandle>,
 }
 impl Module for Example {
-    type ListeningTo = (Render2DEvent, StartEvent);
+    type ListeningTo = (Render2DEvent, StartEvent, RealTimeUpdate);
     fn new(ctx: &mut Engine) -> AnyResult<Self> {
         ctx.dependency::<Renderer2DModule>()?;
+        ctx.dependency::<ClockModule>()?;
 
         Ok(Self {
-            time: Instant::now(),
+            time: Duration::ZERO,
             characters_sheet: None,
         })
     }
@@ -35,9 +36,15 @@ impl Listener<StartEvent> for Example {
     }
 }
 
+impl Listener<RealTimeUpdate> for Example {
+    fn on_event(&mut self, event: &mut RealTimeUpdate, _: &mut EventQueue) {
+        self.time = event.elapsed;
+    }
+}
+
 impl Listener<Render2DEvent> for Example {
     fn on_event(&mut self, _: &mut Render2DEvent, queue: &mut EventQueue) {
-        let rotation = Rad(self.time.elapsed().subsec_millis() as f32 * PI / 500.);
+        let rotation = Rad(self.time.subsec_millis() as f32 * PI / 500.);
 
         let mut draw = Draw2d(queue);
         draw.sprite_centered(
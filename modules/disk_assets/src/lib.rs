@@ -7,7 +7,7 @@ use std::{
 
 use rgine_assets::AssetsEventQueueExt;
 use rgine_logger::warn;
-use rgine_modules::events::EventQueue;
+use rgine_modules::{events::EventQueue, tasks::unblock};
 
 pub trait FileAssetsRegistry: 'static {
     type Handle;
@@ -18,6 +18,7 @@ pub trait FileAssetsRegistry: 'static {
     fn file_extensions() -> &'static [&'static str];
 }
 
+#[derive(Default)]
 pub struct AssetLookup {
     map: HashMap<TypeId, Box<dyn Any>>,
 }
@@ -27,6 +28,19 @@ impl AssetLookup {
         self.map.insert(TypeId::of::<R>(), Box::new(map));
     }
 
+    /// Adds the assets loaded by [`FileAssetsEventQueueExt::load_asset_registry_from_disk_async`]
+    /// and loads their registry, does nothing if they were already added.
+    pub fn add_loaded<R: FileAssetsRegistry>(
+        &mut self,
+        loaded: &mut DiskAssetsLoadedEvent<R>,
+        queue: &mut EventQueue,
+    ) {
+        if let Some((registry, handles)) = loaded.inner.take() {
+            self.add_assets::<R>(handles);
+            queue.load_asset(registry);
+        }
+    }
+
     fn get<R: FileAssetsRegistry>(&self, key: &str) -> &R::Handle {
         self.map
             .get(&TypeId::of::<R>())
@@ -44,12 +58,25 @@ impl AssetLookup {
     }
 }
 
+/// Sent once the files requested with [`FileAssetsEventQueueExt::load_asset_registry_from_disk_async`]
+/// have been read, see [`AssetLookup::add_loaded`].
+pub struct DiskAssetsLoadedEvent<R: FileAssetsRegistry> {
+    inner: Option<(R, HashMap<String, R::Handle>)>,
+}
+
 pub trait FileAssetsEventQueueExt {
+    /// Reads the files of `assets/<path>` with one of the [`FileAssetsRegistry::file_extensions`],
+    /// directories and other files are skipped.
     fn load_asset_registry_from_disk<R: FileAssetsRegistry>(
         &mut self,
         path: &str,
         lookup: &mut AssetLookup,
     );
+
+    /// Reads the files off the main thread, a [`DiskAssetsLoadedEvent<R>`] is sent once they are registered.
+    fn load_asset_registry_from_disk_async<R: FileAssetsRegistry>(&mut self, path: &str)
+    where
+        R::Data: Send;
 }
 impl FileAssetsEventQueueExt for EventQueue {
     fn load_asset_registry_from_disk<R: FileAssetsRegistry>(
@@ -57,42 +84,67 @@ impl FileAssetsEventQueueExt for EventQueue {
         subpath: &str,
         lookup: &mut AssetLookup,
     ) {
-        let mut registry = R::new();
-        let mut lookup_map = HashMap::new();
-        for file in std::fs::read_dir(assets_dir(subpath))
-            .unwrap_or_else(|_| panic!("Could not read assets at {}", assets_dir(subpath)))
-        {
-            let file = file.unwrap();
-            let metadata = file.metadata().unwrap();
-            if !metadata.is_file()
-                || R::file_extensions()
-                    .iter()
-                    .all(|ext| file.path().extension() != Some(OsStr::new(ext)))
-            {
-                return;
-            }
-
-            let name = skip_last(file.file_name().to_string_lossy().split("."))
-                .fold(String::new(), |a, b| a + b);
+        let (registry, lookup_map) = register_files::<R>(read_files::<R>(subpath));
+        lookup.add_assets::<R>(lookup_map);
+        self.load_asset(registry);
+    }
 
-            if !is_snake_case(&name) {
-                warn!(
-                    "Invalid asset name \"{}\" for sprite: {}. assets_name_must_be_written_in_snake_case ! ",
-                    name,
-                    file.path().to_string_lossy()
-                );
-                continue;
+    fn load_asset_registry_from_disk_async<R: FileAssetsRegistry>(&mut self, subpath: &str)
+    where
+        R::Data: Send,
+    {
+        let subpath = subpath.to_owned();
+        self.spawn(async move {
+            let files = unblock(move || read_files::<R>(&subpath)).await;
+            DiskAssetsLoadedEvent {
+                inner: Some(register_files::<R>(files)),
             }
+        });
+    }
+}
 
-            let path = file.path().to_string_lossy().into_owned();
-            let data = R::read_file(path.as_ref());
+fn read_files<R: FileAssetsRegistry>(subpath: &str) -> Vec<(String, R::Data)> {
+    let mut files = Vec::new();
+    for file in std::fs::read_dir(assets_dir(subpath))
+        .unwrap_or_else(|_| panic!("Could not read assets at {}", assets_dir(subpath)))
+    {
+        let file = file.unwrap();
+        let metadata = file.metadata().unwrap();
+        if !metadata.is_file()
+            || R::file_extensions()
+                .iter()
+                .all(|ext| file.path().extension() != Some(OsStr::new(ext)))
+        {
+            continue;
+        }
 
-            lookup_map.insert(name, registry.register(data));
+        let name = skip_last(file.file_name().to_string_lossy().split("."))
+            .fold(String::new(), |a, b| a + b);
+
+        if !is_snake_case(&name) {
+            warn!(
+                "Invalid asset name \"{}\" for sprite: {}. assets_name_must_be_written_in_snake_case ! ",
+                name,
+                file.path().to_string_lossy()
+            );
+            continue;
         }
 
-        lookup.add_assets::<R>(lookup_map);
-        self.load_asset(registry);
+        let path = file.path().to_string_lossy().into_owned();
+        files.push((name, R::read_file(path.as_ref())));
     }
+    files
+}
+
+fn register_files<R: FileAssetsRegistry>(
+    files: Vec<(String, R::Data)>,
+) -> (R, HashMap<String, R::Handle>) {
+    let mut registry = R::new();
+    let lookup_map = files
+        .into_iter()
+        .map(|(name, data)| (name, registry.register(data)))
+        .collect();
+    (registry, lookup_map)
 }
 
 pub fn assets_dir(subpath: &str) -> String {
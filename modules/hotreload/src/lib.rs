@@ -0,0 +1,304 @@
+//! Hot reloading of modules compiled as dynamic libraries.
+//!
+//! # How does it work?
+//! - The module lives in its own crate with `crate-type = ["cdylib"]` and exports itself with [`export_module!`].
+//! - The host loads it with [`HotReloadEngineExt::hot_reload`], which loads the [`HotReloadModule`] watching the library file.
+//! - Whenever the library is rebuilt, the module state is captured with its [`SnapshotModule`] implementation,
+//!   the module is unloaded, the new library is loaded and the state is restored.
+//!
+//! # Limitations
+//! - The host and the library must be built by the same compiler, with the same profile and the same rgine features,
+//!   as modules and engine are shared across the library boundary using the Rust ABI and identified by their `TypeId`.
+//!   In practice the library should be a member of the host workspace, built with `cargo build -p <library>`.
+//! - Hot reloaded modules can't be depended on by other modules, otherwise they can't be unloaded.
+//! - Events defined in the library must not be deferred across a reload, their layout may have changed in the new library.
+//! - Libraries are never unloaded, each reload keeps the previous library mapped in memory.
+//!   The engine keeps `&'static str` type names that point into them for as long as it lives:
+//!   in the module names and ids, the listeners, the snapshot hooks, the soft dependencies, the profiler spans
+//!   and the [`ModuleInfo`](rgine_modules::introspection::ModuleInfo)s.
+
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use libloading::Library;
+use rgine_logger::{error, info, warn};
+use rgine_modules::{
+    events::{EventQueue, Listener},
+    snapshot::SnapshotModule,
+    standards::PlatformUpdateEvent,
+    AnyResult, Engine, Module, ModuleError,
+};
+
+/// Exports the module `$module` from a dynamic library, to be loaded with [`HotReloadEngineExt::hot_reload`].
+///
+/// The module must implement [`SnapshotModule`] for its state to survive reloads.
+#[macro_export]
+macro_rules! export_module {
+    ($module:ty) => {
+        #[no_mangle]
+        pub fn rgine_hotreload_module() -> $crate::HotModule {
+            $crate::HotModule::new::<$module>()
+        }
+    };
+}
+
+const EXPORT_SYMBOL: &[u8] = b"rgine_hotreload_module";
+/// Minimum time between two checks of the library files
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Entry points of a hot reloadable module, created by [`export_module!`].
+#[derive(Clone, Copy)]
+pub struct HotModule {
+    name: &'static str,
+    version: u32,
+    load: fn(&mut Engine) -> Result<(), ModuleError>,
+    unload: fn(&mut Engine) -> Result<Vec<u8>, ModuleError>,
+    restore: fn(&mut Engine, &[u8]) -> AnyResult<()>,
+}
+
+impl HotModule {
+    pub fn new<T: SnapshotModule>() -> Self {
+        Self {
+            name: std::any::type_name::<T>(),
+            version: T::VERSION,
+            load: |engine| engine.dependency::<T>().map(drop),
+            unload: |engine| {
+                let state = engine.unload_module::<T>()?;
+                Ok(
+                    serde_json::to_vec(&state.snapshot())
+                        .expect("Failed to serialize module state"),
+                )
+            },
+            restore: |engine, snapshot| {
+                let snapshot = serde_json::from_slice(snapshot)?;
+                engine
+                    .dependency_mut::<T>()?
+                    .write_state()
+                    .restore(snapshot);
+                Ok(())
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Error relative to hot reloading
+pub enum HotReloadError {
+    /// Error occured while copying the library file
+    Io(std::io::Error),
+    /// Error occured while loading the library, or because it doesn't export a module
+    Library(libloading::Error),
+    /// Error occured while loading the exported module
+    Module(ModuleError),
+}
+
+impl Display for HotReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to copy the library: {}", e),
+            Self::Library(e) => write!(f, "Failed to load the library: {}", e),
+            Self::Module(e) => write!(f, "Failed to load the module: {}", e),
+        }
+    }
+}
+
+impl Error for HotReloadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Library(e) => Some(e),
+            Self::Module(e) => Some(e),
+        }
+    }
+}
+
+pub trait HotReloadEngineExt {
+    /// Loads the module exported by the dynamic library at `path` and reloads it whenever the file changes.
+    fn hot_reload(&mut self, path: impl AsRef<Path>) -> Result<(), HotReloadError>;
+}
+
+impl HotReloadEngineExt for Engine {
+    fn hot_reload(&mut self, path: impl AsRef<Path>) -> Result<(), HotReloadError> {
+        // Loaded first so that it is unloaded last, along with the libraries
+        let reloader = self
+            .dependency_mut::<HotReloadModule>()
+            .map_err(HotReloadError::Module)?;
+        let library = HotLibrary::open(path.as_ref().to_path_buf(), 0)?;
+        (library.module.load)(self).map_err(HotReloadError::Module)?;
+        reloader.write_state().libraries.push(library);
+        Ok(())
+    }
+}
+
+/// Watches the libraries loaded with [`HotReloadEngineExt::hot_reload`], on every platform update.
+pub struct HotReloadModule {
+    libraries: Vec<HotLibrary>,
+    last_poll: Instant,
+}
+
+impl Module for HotReloadModule {
+    type ListeningTo = (PlatformUpdateEvent,);
+
+    fn new(_: &mut Engine) -> AnyResult<Self> {
+        Ok(Self {
+            libraries: Vec::new(),
+            last_poll: Instant::now(),
+        })
+    }
+}
+
+impl Listener<PlatformUpdateEvent> for HotReloadModule {
+    fn on_event(&mut self, _: &mut PlatformUpdateEvent, queue: &mut EventQueue) {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return;
+        }
+        self.last_poll = Instant::now();
+
+        for (index, library) in self.libraries.iter_mut().enumerate() {
+            let Ok(modified) = modified(&library.path) else {
+                continue;
+            };
+            if modified == library.modified {
+                continue;
+            }
+            // Wait for the file to stop changing, the compiler may still be writing it
+            if library.changed != Some(modified) {
+                library.changed = Some(modified);
+                continue;
+            }
+            queue.push_command(move |engine| reload(engine, index));
+        }
+    }
+}
+
+struct HotLibrary {
+    path: PathBuf,
+    /// Copy of the library actually loaded, so that the original can be overwritten
+    loaded_path: PathBuf,
+    modified: SystemTime,
+    changed: Option<SystemTime>,
+    generation: usize,
+    module: HotModule,
+}
+
+impl HotLibrary {
+    fn open(path: PathBuf, generation: usize) -> Result<Self, HotReloadError> {
+        let modified = modified(&path).map_err(HotReloadError::Io)?;
+        let loaded_path = loaded_path(&path, generation);
+        std::fs::create_dir_all(loaded_path.parent().unwrap()).map_err(HotReloadError::Io)?;
+        std::fs::copy(&path, &loaded_path).map_err(HotReloadError::Io)?;
+
+        // SAFETY: The library is expected to export a module with `export_module!`,
+        // built with the same compiler and rgine version as the host.
+        let (library, module) = unsafe {
+            let library = Library::new(&loaded_path).map_err(HotReloadError::Library)?;
+            let export = library
+                .get::<fn() -> HotModule>(EXPORT_SYMBOL)
+                .map_err(HotReloadError::Library)?;
+            let module = export();
+            (library, module)
+        };
+        // Never unloaded, as the engine keeps references to the type names of the library, see the limitations
+        std::mem::forget(library);
+
+        Ok(Self {
+            path,
+            loaded_path,
+            modified,
+            changed: None,
+            generation,
+            module,
+        })
+    }
+
+    /// Removes the copy of the library, which stays loaded, where the platform allows it.
+    fn close(self) {
+        let _ = std::fs::remove_file(self.loaded_path);
+    }
+}
+
+fn modified(path: &Path) -> std::io::Result<SystemTime> {
+    std::fs::metadata(path)?.modified()
+}
+
+fn loaded_path(path: &Path, generation: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    std::env::temp_dir().join("rgine_hotreload").join(format!(
+        "{}-{}-{}.{}",
+        stem,
+        std::process::id(),
+        generation,
+        extension
+    ))
+}
+
+/// Replaces the module of the library `index` with the one of the rebuilt library,
+/// falling back to the old one if the new one fails to load.
+fn reload(engine: &mut Engine, index: usize) {
+    let reloader = engine.dependency_mut::<HotReloadModule>().unwrap();
+    let (path, generation, old_module) = {
+        let state = reloader.read_state();
+        let library = &state.libraries[index];
+        (library.path.clone(), library.generation + 1, library.module)
+    };
+
+    let snapshot = match (old_module.unload)(engine) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            error!(
+                "Failed to unload {} for hot reloading: {}",
+                old_module.name, e
+            );
+            let mut state = reloader.write_state();
+            let library = &mut state.libraries[index];
+            library.modified = modified(&path).unwrap_or(library.modified);
+            library.changed = None;
+            return;
+        }
+    };
+
+    let reloaded = HotLibrary::open(path, generation).and_then(|library| {
+        (library.module.load)(engine).map_err(HotReloadError::Module)?;
+        Ok(library)
+    });
+    let (library, module) = match reloaded {
+        Ok(library) => {
+            let module = library.module;
+            (Some(library), module)
+        }
+        Err(e) => {
+            error!("Failed to hot reload {}: {}", old_module.name, e);
+            (old_module.load)(engine).expect("Failed to load back the previous module");
+            (None, old_module)
+        }
+    };
+
+    if module.version == old_module.version {
+        if let Err(e) = (module.restore)(engine, &snapshot) {
+            error!("Failed to restore the state of {}: {}", module.name, e);
+        }
+    } else {
+        warn!(
+            "The snapshot version of {} changed from {} to {}, its state was reset",
+            module.name, old_module.version, module.version
+        );
+    }
+
+    let mut state = reloader.write_state();
+    match library {
+        Some(library) => {
+            std::mem::replace(&mut state.libraries[index], library).close();
+            info!("Hot reloaded {}", module.name);
+        }
+        None => {
+            let library = &mut state.libraries[index];
+            library.modified = modified(&library.path).unwrap_or(library.modified);
+            library.changed = None;
+        }
+    }
+}
@@ -2,21 +2,26 @@ use cgmath::{Array, Matrix3, Vector2};
 use renderer::SpriteRenderer;
 use rgine_assets::AssetsModule;
 use rgine_graphics::{
-    GraphicsModule, PreSubmitRenderEvent, SubmitRenderEvent, SurfaceResizeEvent, WindowReadyEvent,
+    color::Color3, GraphicsModule, PreSubmitRenderEvent, SubmitRenderEvent, SurfaceResizeEvent,
+    WindowReadyEvent,
 };
 use rgine_modules::{
-    events::{EventQueue, Listener},
+    events::{EventQueue, Listener, ListenerOrder},
+    tasks::unblock,
     AnyResult, Dependency, Engine, Module,
 };
 
-use texture::{DrawParams, Sprite, SpriteSheetsRegistry};
+use texture::{DrawParams, PackedAtlas, Sprite, SpriteSheetsRegistry};
 
 mod renderer;
 pub mod texture;
 
 pub mod prelude {
     pub use crate::{
-        texture::{DrawParams, Sprite, SpriteSheetData, SpriteSheetHandle, SpriteSheetsRegistry},
+        texture::{
+            DrawParams, PackedAtlas, Sprite, SpriteSheetData, SpriteSheetHandle,
+            SpriteSheetsRegistry,
+        },
         Draw2d, Render2DEvent, Renderer2DModule,
     };
 }
@@ -28,18 +33,63 @@ pub struct DrawSpriteEvent {
 }
 pub struct RegisterSpriteEvent;
 pub struct RefreshRenderer2DEvent;
+/// Sent once the atlas requested by [`RefreshRenderer2DEvent`] has been packed in the background
+pub struct AtlasPackedEvent {
+    atlas: Option<PackedAtlas>,
+    /// Number of the refresh that requested the atlas, packs can finish out of order
+    generation: u64,
+}
 
 pub struct Renderer2DModule {
     graphics: Dependency<GraphicsModule>,
     asset_loader: Dependency<AssetsModule>,
+    clear_color: Color3,
 
     renderer: Option<SpriteRenderer>,
+    /// Generation of the last requested atlas, see [`AtlasPackedEvent`]
+    requested_atlas: u64,
+    /// Generation of the atlas used by the renderer, older atlases are ignored
+    current_atlas: u64,
+}
+
+/// Section `[renderer_2d]` of the engine configuration
+#[cfg(feature = "config")]
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default)]
+pub struct Renderer2DConfig {
+    /// Color of the background, as linear RGB components between 0 and 1
+    pub clear_color: [f32; 3],
+}
+
+#[cfg(feature = "config")]
+impl Default for Renderer2DConfig {
+    fn default() -> Self {
+        Self {
+            clear_color: DEFAULT_CLEAR_COLOR.into(),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl rgine_modules::config::ModuleConfig for Renderer2DConfig {
+    const SECTION: &'static str = "renderer_2d";
+
+    fn validate(&self) -> Result<(), String> {
+        if self.clear_color.iter().all(|c| (0.0..=1.0).contains(c)) {
+            Ok(())
+        } else {
+            Err("the clear color components must be between 0 and 1".to_owned())
+        }
+    }
 }
 
+const DEFAULT_CLEAR_COLOR: Color3 = Color3::gray(0.01);
+
 impl Module for Renderer2DModule {
     type ListeningTo = (
         WindowReadyEvent,
         RefreshRenderer2DEvent,
+        AtlasPackedEvent,
         PreSubmitRenderEvent,
         SubmitRenderEvent,
         SurfaceResizeEvent,
@@ -49,10 +99,21 @@ impl Module for Renderer2DModule {
         let graphics = ctx.dependency::<GraphicsModule>()?;
         let asset_loader = ctx.dependency::<AssetsModule>()?;
 
+        #[cfg(feature = "config")]
+        let clear_color = {
+            let [r, g, b] = ctx.config::<Renderer2DConfig>()?.clear_color;
+            Color3::rgb(r, g, b)
+        };
+        #[cfg(not(feature = "config"))]
+        let clear_color = DEFAULT_CLEAR_COLOR;
+
         Ok(Self {
             graphics,
             asset_loader,
+            clear_color,
             renderer: None,
+            requested_atlas: 0,
+            current_atlas: 0,
         })
     }
 }
@@ -61,16 +122,45 @@ impl Listener<WindowReadyEvent> for Renderer2DModule {
     fn on_event(&mut self, _: &mut WindowReadyEvent, queue: &mut EventQueue) {
         queue.push(RefreshRenderer2DEvent);
     }
+
+    fn order() -> ListenerOrder {
+        ListenerOrder::new().after::<GraphicsModule>()
+    }
 }
 
 impl Listener<RefreshRenderer2DEvent> for Renderer2DModule {
-    fn on_event(&mut self, _: &mut RefreshRenderer2DEvent, _: &mut EventQueue) {
+    fn on_event(&mut self, _: &mut RefreshRenderer2DEvent, queue: &mut EventQueue) {
+        let registry = self
+            .asset_loader
+            .read_state()
+            .get::<SpriteSheetsRegistry>()
+            .clone();
+        self.requested_atlas += 1;
+        let generation = self.requested_atlas;
+        queue.spawn(async move {
+            AtlasPackedEvent {
+                atlas: Some(unblock(move || registry.pack()).await),
+                generation,
+            }
+        });
+    }
+}
+
+impl Listener<AtlasPackedEvent> for Renderer2DModule {
+    fn on_event(&mut self, event: &mut AtlasPackedEvent, _: &mut EventQueue) {
+        if event.generation <= self.current_atlas {
+            return;
+        }
+        let Some(atlas) = event.atlas.take() else {
+            return;
+        };
+        self.current_atlas = event.generation;
         let g = self.graphics.read_state();
-        let assets = self.asset_loader.read_state();
         self.renderer.replace(SpriteRenderer::new(
             g.ctx.as_ref().unwrap(),
             g.window_size().unwrap(),
-            assets.get::<SpriteSheetsRegistry>().clone(),
+            atlas,
+            self.clear_color,
         ));
     }
 }
@@ -100,14 +190,18 @@ impl Listener<SurfaceResizeEvent> for Renderer2DModule {
             renderer.resize(ctx, g.window_size().unwrap())
         }
     }
+
+    fn order() -> ListenerOrder {
+        ListenerOrder::new().after::<GraphicsModule>()
+    }
 }
 
 impl Listener<DrawSpriteEvent> for Renderer2DModule {
     fn on_event(&mut self, event: &mut DrawSpriteEvent, _: &mut EventQueue) {
-        self.renderer
-            .as_mut()
-            .unwrap()
-            .draw(event.sprite.clone(), event.params.clone());
+        // Sprites drawn while the atlas is still being packed are skipped
+        if let Some(renderer) = &mut self.renderer {
+            renderer.draw(event.sprite.clone(), event.params.clone());
+        }
     }
 }
 
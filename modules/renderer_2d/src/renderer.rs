@@ -9,7 +9,7 @@ use rgine_graphics::{
 };
 use wgpu::{util::StagingBelt, *};
 
-use crate::texture::{Atlas, DrawParams, Sprite, SpriteSheetsRegistry};
+use crate::texture::{Atlas, DrawParams, PackedAtlas, Sprite};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
@@ -32,6 +32,7 @@ pub struct SpriteRenderer {
     sprite_staging_belt: StagingBelt,
 
     proj_matrix: Matrix3<f32>,
+    clear_color: Color3,
     atlas: Atlas,
     queue: Vec<SpriteInstance>,
 }
@@ -43,7 +44,8 @@ impl SpriteRenderer {
     pub fn new(
         ctx: &GraphicsCtx,
         window_size: (u32, u32),
-        sprite_registry: SpriteSheetsRegistry,
+        atlas: PackedAtlas,
+        clear_color: Color3,
     ) -> Self {
         let (sprite_pipeline, texture_bind_group_layout) =
             create_sprite_pipeline(&ctx.device, ctx.surface_texture_format);
@@ -56,7 +58,7 @@ impl SpriteRenderer {
 
         let queue = Vec::with_capacity(MAX_SPRITES_PER_BATCH as usize);
 
-        let atlas = sprite_registry.build_atlas(ctx, &texture_bind_group_layout);
+        let atlas = atlas.upload(ctx, &texture_bind_group_layout);
 
         let proj_matrix = compute_proj_matrix(window_size);
 
@@ -70,6 +72,7 @@ impl SpriteRenderer {
             sprite_staging_belt,
             sprite_instance_buf,
             proj_matrix,
+            clear_color,
             queue,
             atlas,
         }
@@ -136,7 +139,7 @@ impl SpriteRenderer {
                         view: &frame.view,
                         resolve_target: None,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(Color3::gray(0.01).into()),
+                            load: wgpu::LoadOp::Clear(self.clear_color.into()),
                             store: wgpu::StoreOp::Store,
                         },
                     })],
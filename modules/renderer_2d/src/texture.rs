@@ -15,6 +15,29 @@ pub struct Atlas {
     pub(crate) bind_group: BindGroup,
 }
 
+/// Atlas packed on the CPU, not uploaded to the GPU yet
+pub struct PackedAtlas {
+    sheets: Vec<SpriteSheet>,
+    size: Vector2<u32>,
+    image: Vec<u8>,
+}
+
+impl PackedAtlas {
+    pub(super) fn upload(
+        self,
+        ctx: &GraphicsCtx,
+        texture_bind_group_layout: &BindGroupLayout,
+    ) -> Atlas {
+        let (_texture, bind_group) =
+            create_texture(ctx, self.size, self.image, texture_bind_group_layout);
+
+        Atlas {
+            sheets: self.sheets,
+            bind_group,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SpriteSheetData {
     pub path: String,
@@ -95,11 +118,9 @@ impl SpriteSheetsRegistry {
         SpriteSheetHandle(self.to_load.len() - 1)
     }
 
-    pub(super) fn build_atlas(
-        self,
-        ctx: &GraphicsCtx,
-        texture_bind_group_layout: &BindGroupLayout,
-    ) -> Atlas {
+    /// Reads and packs the registered sprite(sheet)s into a single image, this blocks on the disk
+    /// and should be ran off the main thread.
+    pub fn pack(self) -> PackedAtlas {
         let mut packer = TexturePacker::new_skyline(TexturePackerConfig {
             max_width: 4096,
             max_height: 4096,
@@ -151,10 +172,11 @@ impl SpriteSheetsRegistry {
             .to_rgba8();
         let size: Vector2<u32> = image.dimensions().into();
 
-        let (_texture, bind_group) =
-            create_texture(ctx, size, image.into_vec(), texture_bind_group_layout);
-
-        Atlas { sheets, bind_group }
+        PackedAtlas {
+            sheets,
+            size,
+            image: image.into_vec(),
+        }
     }
 }
 
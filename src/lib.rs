@@ -9,12 +9,18 @@ pub use rgine_disk_assets as disk_assets;
 #[cfg(feature = "2d")]
 pub use rgine_renderer_2d as renderer_2d;
 
+#[cfg(feature = "hotreload")]
+pub use rgine_hotreload as hotreload;
+
 pub mod prelude {
     pub use crate::{assets::AssetsEventQueueExt, maths::*, modules::prelude::*};
 
     #[cfg(feature = "asset_loader")]
     pub use crate::disk_assets::FileAssetsEventQueueExt;
 
+    #[cfg(feature = "headless")]
+    pub use crate::platform::headless::{HeadlessPlatformConfig, HeadlessPlatformEngineExt};
+
     #[cfg(feature = "graphics")]
     pub use crate::{
         graphics::color::Color3,